@@ -0,0 +1,265 @@
+//! Command-line interface to the library, enabled with the `cli` feature. `versian compare`
+//! follows the semantics and exit codes of `dpkg --compare-versions`.
+
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use versian::optional::{EmptyVersion, OptionalVersion};
+use versian::options::ParseOptions;
+use versian::relation::RelationOperator;
+use versian::serde::Structured;
+use versian::DebianVersion;
+
+const USAGE: &str = "\
+Usage: versian <command> [<argument>...]
+
+Commands:
+  compare <version> <operator> <version>
+      Exits with 0 if the relation holds and 1 otherwise. Operators are
+      lt le eq ne ge gt, the deprecated << <= = >= >> < >, and lt-nl le-nl
+      ge-nl gt-nl, which treat an empty version as newer than all others.
+  parse <version>...
+      Prints the components of every version as a JSON object.
+  sort [-r|--reverse]
+      Reads versions from standard input, one per line, and prints them sorted.
+  validate <version>...
+      Checks that the versions follow the Debian Policy Manual.
+
+Malformed versions and usage errors exit with 2.";
+
+/// Exit status for usage errors and versions that can't be parsed, like `dpkg` uses.
+const EXIT_ERROR: u8 = 2;
+
+/// An error ending the program with [`EXIT_ERROR`].
+struct Failure(String);
+
+impl From<io::Error> for Failure {
+    fn from(error: io::Error) -> Self {
+        Failure(error.to_string())
+    }
+}
+
+/// An operator of `dpkg --compare-versions`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Operator {
+    /// `None` for `ne`, which has no counterpart in relationship fields.
+    relation: Option<RelationOperator>,
+    /// Where the empty version sorts, `Latest` for the `-nl` operators.
+    empty: EmptyVersion,
+}
+
+impl Operator {
+    fn parse(s: &str) -> Option<Self> {
+        let (name, empty_is_newest) = match s.strip_suffix("-nl") {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        let relation = match name {
+            "lt" => Some(RelationOperator::StrictlyEarlier),
+            "le" => Some(RelationOperator::EarlierOrEqual),
+            "eq" => Some(RelationOperator::Exactly),
+            "ne" if !empty_is_newest => None,
+            "ge" => Some(RelationOperator::LaterOrEqual),
+            "gt" => Some(RelationOperator::StrictlyLater),
+            _ if !empty_is_newest => Some(name.parse().ok()?),
+            _ => return None,
+        };
+        if empty_is_newest && relation == Some(RelationOperator::Exactly) {
+            return None;
+        }
+
+        Some(Self {
+            relation,
+            empty: if empty_is_newest {
+                EmptyVersion::Latest
+            } else {
+                EmptyVersion::Earliest
+            },
+        })
+    }
+
+    /// Returns `true` if `a` relates to `b` as required.
+    fn holds(&self, a: &OptionalVersion, b: &OptionalVersion) -> bool {
+        let ordering = a.cmp_with(b, self.empty);
+
+        match self.relation {
+            Some(relation) => relation.matches(ordering),
+            None => ordering != Ordering::Equal,
+        }
+    }
+}
+
+/// Parses `s` like `dpkg` does, printing tolerated problems as warnings.
+fn parse(s: &str) -> Result<DebianVersion, Failure> {
+    let (version, warnings) = DebianVersion::parse_with(s, &ParseOptions::dpkg())
+        .map_err(|error| Failure(format!("Version {:?} is invalid: {}", s, error)))?;
+    for warning in warnings {
+        eprintln!("versian: warning: Version {:?} is invalid: {}", s, warning);
+    }
+
+    Ok(version)
+}
+
+/// Parses a version given to `compare`, where empty strings and `<unknown>` denote the empty
+/// version.
+fn parse_optional(s: &str) -> Result<OptionalVersion, Failure> {
+    if s.is_empty() || s == "<unknown>" {
+        Ok(OptionalVersion::NONE)
+    } else {
+        parse(s).map(OptionalVersion::from)
+    }
+}
+
+fn compare(args: &[String]) -> Result<ExitCode, Failure> {
+    let [a, operator, b] = args else {
+        return Err(Failure(
+            "compare takes three arguments: <version> <operator> <version>".to_string(),
+        ));
+    };
+    let operator = Operator::parse(operator)
+        .ok_or_else(|| Failure(format!("Unknown operator {:?}.", operator)))?;
+    if operator
+        .relation
+        .is_some_and(|relation| relation.is_deprecated())
+    {
+        eprintln!("versian: warning: Operator {:?} is deprecated.", args[1]);
+    }
+
+    let holds = operator.holds(&parse_optional(a)?, &parse_optional(b)?);
+    Ok(if holds {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn parse_command(args: &[String]) -> Result<ExitCode, Failure> {
+    let mut stdout = io::stdout().lock();
+
+    for arg in args {
+        let json = serde_json::to_string(&Structured(parse(arg)?))
+            .map_err(|error| Failure(error.to_string()))?;
+        writeln!(stdout, "{}", json)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn sort(args: &[String]) -> Result<ExitCode, Failure> {
+    let reverse = match args {
+        [] => false,
+        [flag] if flag == "-r" || flag == "--reverse" => true,
+        _ => return Err(Failure("sort takes no arguments except -r".to_string())),
+    };
+
+    let mut versions = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            versions.push((parse(line)?, line.to_string()));
+        }
+    }
+
+    versions.sort_by(|(a, _), (b, _)| a.cmp(b));
+    if reverse {
+        versions.reverse();
+    }
+
+    let mut stdout = io::stdout().lock();
+    for (_, line) in versions {
+        writeln!(stdout, "{}", line)?;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn validate(args: &[String]) -> Result<ExitCode, Failure> {
+    let mut valid = true;
+
+    for arg in args {
+        if let Err(error) = arg.parse::<DebianVersion>() {
+            eprintln!("{}: {}", arg, error);
+            valid = false;
+        }
+    }
+
+    Ok(if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("compare") => compare(&args[1..]),
+        Some("parse") => parse_command(&args[1..]),
+        Some("sort") => sort(&args[1..]),
+        Some("validate") => validate(&args[1..]),
+        Some("-h" | "--help" | "help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(Failure(USAGE.to_string())),
+    };
+
+    result.unwrap_or_else(|Failure(message)| {
+        eprintln!("versian: {}", message);
+        ExitCode::from(EXIT_ERROR)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn holds(a: &str, operator: &str, b: &str) -> bool {
+        Operator::parse(operator).unwrap().holds(
+            &parse_optional(a).ok().unwrap(),
+            &parse_optional(b).ok().unwrap(),
+        )
+    }
+
+    #[test]
+    fn operators() {
+        for operator in ["lt", "<<", "le", "<=", "<", "lt-nl", "le-nl"] {
+            assert!(holds("1.0-1", operator, "1.0-2"), "{}", operator);
+            assert!(!holds("1.0-2", operator, "1.0-1"), "{}", operator);
+        }
+        for operator in ["gt", ">>", "ge", ">=", ">", "gt-nl", "ge-nl"] {
+            assert!(holds("1:0.1", operator, "2.0"), "{}", operator);
+        }
+        assert!(holds("1.01", "eq", "1.1"));
+        assert!(holds("1.0", "ne", "1.0~rc1"));
+
+        for invalid in ["eq-nl", "ne-nl", "lt-nl-nl", "==", "-nl", ""] {
+            assert_eq!(Operator::parse(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn empty_versions() {
+        assert!(holds("", "lt", "~~"));
+        assert!(holds("<unknown>", "lt", "0"));
+        assert!(!holds("", "eq", "0"));
+        assert!(holds("", "eq", ""));
+        assert!(!holds("", "ne", ""));
+
+        assert!(holds("", "gt-nl", "1"));
+        assert!(holds("1", "lt-nl", ""));
+        assert!(!holds("", "lt-nl", ""));
+        assert!(holds("", "le-nl", ""));
+    }
+
+    #[test]
+    fn malformed_versions() {
+        assert!(parse("a1.0").is_ok());
+        assert!(parse("1 2").is_err());
+        assert!(parse("1:").is_err());
+    }
+}
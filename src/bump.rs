@@ -0,0 +1,249 @@
+//! Helpers deriving the next [`DebianVersion`](crate::DebianVersion) for common kinds of uploads,
+//! following the conventions of the Debian Developer's Reference.
+//!
+//! Suffixes are appended to the Debian revision, or to the upstream version of native packages,
+//! which don't have a revision.
+
+use crate::validations::ValidateUpstreamVersion;
+use crate::{DebianVersion, Result};
+
+impl DebianVersion {
+    /// Returns the version of the next maintainer upload, e.g. `1.2-3` → `1.2-4`. NMU, binNMU
+    /// and other suffixes are dropped, so `1.2-3.1` → `1.2-4` as well.
+    ///
+    /// Returns `None` for native versions, revisions not starting with a number and revisions
+    /// whose number can't be incremented without overflowing.
+    pub fn next_revision(&self) -> Option<DebianVersion> {
+        let revision = self.debian_revision.as_deref()?;
+        let digits = revision
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(revision.len());
+        let number = revision[..digits].parse::<u64>().ok()?;
+
+        Some(DebianVersion {
+            debian_revision: Some(number.checked_add(1)?.to_string()),
+            ..self.clone()
+        })
+    }
+
+    /// Returns the version of a new upstream release, keeping the epoch. Non-native versions get
+    /// the Debian revision `1`.
+    pub fn new_upstream_version(&self, upstream_version: &str) -> Result<DebianVersion> {
+        let debian_revision = self.debian_revision.as_ref().map(|_| "1".to_string());
+        if debian_revision.is_some() {
+            upstream_version.validate_with_revision()?;
+        } else {
+            upstream_version.validate_without_revision()?;
+        }
+
+        Ok(DebianVersion {
+            epoch: self.epoch,
+            upstream_version: upstream_version.to_string(),
+            debian_revision,
+        })
+    }
+
+    /// Returns the version of a non-maintainer upload: `1.2-3` → `1.2-3.1` and `1.2-3.1` →
+    /// `1.2-3.2` for non-native versions, `1.2` → `1.2+nmu1` for native ones. A binNMU suffix is
+    /// dropped, so `1.2-3+b1` → `1.2-3.1`.
+    pub fn nmu(&self) -> DebianVersion {
+        let mut version = self.clone();
+        match version.debian_revision {
+            Some(ref mut revision) => strip_binnmu_suffix(revision),
+            None => strip_binnmu_suffix(&mut version.upstream_version),
+        }
+
+        match version.debian_revision {
+            Some(ref mut revision) => match revision.split_once('.') {
+                Some((maintainer, nmu)) if is_number(maintainer) && is_number(nmu) => {
+                    bump_suffix(revision, ".");
+                }
+                _ => revision.push_str(".1"),
+            },
+            None => bump_suffix(&mut version.upstream_version, "+nmu"),
+        }
+
+        version
+    }
+
+    /// Returns the version of a binary-only NMU, e.g. `1.2-3` → `1.2-3+b1` and `1.2-3+b1` →
+    /// `1.2-3+b2`.
+    pub fn binnmu(&self) -> DebianVersion {
+        self.with_bumped_suffix("+b")
+    }
+
+    /// Returns the version of a backport to the Debian release with the given major version,
+    /// e.g. `1.2-3` → `1.2-3~bpo12+1` and `1.2-3~bpo12+1` → `1.2-3~bpo12+2`.
+    pub fn backport(&self, release: u32) -> DebianVersion {
+        self.with_bumped_suffix(&format!("~bpo{}+", release))
+    }
+
+    /// Returns the version of a security or stable update for the Debian release with the given
+    /// major version, e.g. `1.2-3` → `1.2-3+deb12u1` and `1.2-3+deb12u1` → `1.2-3+deb12u2`.
+    pub fn security_update(&self, release: u32) -> DebianVersion {
+        self.with_bumped_suffix(&format!("+deb{}u", release))
+    }
+
+    /// Returns the version with its epoch incremented, a missing epoch becomes `1`. Returns
+    /// `None` if the epoch can't be incremented without overflowing.
+    pub fn bump_epoch(&self) -> Option<DebianVersion> {
+        Some(DebianVersion {
+            epoch: Some(self.epoch.map_or(Some(1), |epoch| epoch.checked_add(1))?),
+            ..self.clone()
+        })
+    }
+
+    fn with_bumped_suffix(&self, marker: &str) -> DebianVersion {
+        let mut version = self.clone();
+
+        match version.debian_revision {
+            Some(ref mut revision) => bump_suffix(revision, marker),
+            None => bump_suffix(&mut version.upstream_version, marker),
+        }
+
+        version
+    }
+}
+
+pub(crate) fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Removes a binNMU suffix such as `+b1` from the end of `s`.
+fn strip_binnmu_suffix(s: &mut String) {
+    if let Some(index) = s.rfind("+b") {
+        if is_number(&s[index + 2..]) {
+            s.truncate(index);
+        }
+    }
+}
+
+/// Increments the number following the last `marker` in `s`, or appends `marker` followed by `1`
+/// if `s` doesn't end with such a suffix or its number can't be incremented without overflowing.
+fn bump_suffix(s: &mut String, marker: &str) {
+    if let Some(index) = s.rfind(marker) {
+        let number = &s[index + marker.len()..];
+
+        if is_number(number) {
+            if let Some(next) = number.parse::<u64>().ok().and_then(|n| n.checked_add(1)) {
+                s.truncate(index + marker.len());
+                s.push_str(&next.to_string());
+                return;
+            }
+        }
+    }
+
+    s.push_str(marker);
+    s.push('1');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DebianVersion;
+    use pretty_assertions::assert_eq;
+
+    fn version(s: &str) -> DebianVersion {
+        s.parse().unwrap()
+    }
+
+    fn bumped<F: Fn(&DebianVersion) -> DebianVersion>(s: &str, f: F) -> String {
+        f(&version(s)).to_string()
+    }
+
+    #[test]
+    fn next_revision() {
+        assert_eq!(version("1.2-3").next_revision(), Some(version("1.2-4")));
+        assert_eq!(
+            version("1:1.2-3.1").next_revision(),
+            Some(version("1:1.2-4"))
+        );
+        assert_eq!(version("1.2-9+b1").next_revision(), Some(version("1.2-10")));
+        assert_eq!(version("1.2").next_revision(), None);
+    }
+
+    #[test]
+    fn new_upstream_version() {
+        assert_eq!(
+            version("2:1.2-3")
+                .new_upstream_version("1.3")
+                .unwrap()
+                .to_string(),
+            "2:1.3-1"
+        );
+        assert_eq!(
+            version("1.2")
+                .new_upstream_version("1.3")
+                .unwrap()
+                .to_string(),
+            "1.3"
+        );
+        assert!(version("1.2-3").new_upstream_version("v1.3").is_err());
+    }
+
+    #[test]
+    fn nmu() {
+        assert_eq!(bumped("1.2-3", DebianVersion::nmu), "1.2-3.1");
+        assert_eq!(bumped("1.2-3.1", DebianVersion::nmu), "1.2-3.2");
+        assert_eq!(bumped("1.2-0ubuntu1", DebianVersion::nmu), "1.2-0ubuntu1.1");
+        assert_eq!(bumped("1.2", DebianVersion::nmu), "1.2+nmu1");
+        assert_eq!(bumped("1.2+nmu1", DebianVersion::nmu), "1.2+nmu2");
+        assert_eq!(bumped("1.2-3+b1", DebianVersion::nmu), "1.2-3.1");
+        assert_eq!(bumped("1.2-3.1+b2", DebianVersion::nmu), "1.2-3.2");
+        assert_eq!(bumped("1.2+b1", DebianVersion::nmu), "1.2+nmu1");
+        assert_eq!(bumped("1.2-3+bpo1", DebianVersion::nmu), "1.2-3+bpo1.1");
+    }
+
+    #[test]
+    fn suffixes() {
+        assert_eq!(bumped("1.2-3", DebianVersion::binnmu), "1.2-3+b1");
+        assert_eq!(bumped("1.2-3+b1", DebianVersion::binnmu), "1.2-3+b2");
+        assert_eq!(bumped("1.2", DebianVersion::binnmu), "1.2+b1");
+        assert_eq!(bumped("1.2-3", |v| v.backport(12)), "1.2-3~bpo12+1");
+        assert_eq!(bumped("1.2-3~bpo12+1", |v| v.backport(12)), "1.2-3~bpo12+2");
+        assert_eq!(bumped("1.2-3", |v| v.security_update(12)), "1.2-3+deb12u1");
+        assert_eq!(
+            bumped("1.2-3+deb12u1", |v| v.security_update(12)),
+            "1.2-3+deb12u2"
+        );
+    }
+
+    #[test]
+    fn suffixes_sort_as_intended() {
+        let base = version("1.2-3");
+
+        assert!(base.backport(12) < base);
+        assert!(base.nmu() > base);
+        assert!(base.binnmu() > base);
+        assert!(base.security_update(12) > base);
+        assert!(base.next_revision().unwrap() > base.nmu());
+        assert!(base.bump_epoch().unwrap() > version("9.9-9"));
+    }
+
+    #[test]
+    fn bump_epoch() {
+        assert_eq!(version("1.2-3").bump_epoch(), Some(version("1:1.2-3")));
+        assert_eq!(version("1:1.2-3").bump_epoch(), Some(version("2:1.2-3")));
+    }
+
+    #[test]
+    fn overflow() {
+        let max = u64::MAX;
+
+        assert_eq!(version(&format!("1.0-{}", max)).next_revision(), None);
+        assert_eq!(
+            bumped(&format!("1.0-1+b{}", max), DebianVersion::binnmu),
+            format!("1.0-1+b{}+b1", max)
+        );
+        assert_eq!(
+            bumped(&format!("1.0-1.{}", max), DebianVersion::nmu),
+            format!("1.0-1.{}.1", max)
+        );
+        assert!(version(&format!("1.0-1+b{}", max)).binnmu() > version(&format!("1.0-1+b{}", max)));
+
+        let epoch = DebianVersion {
+            epoch: Some(usize::MAX),
+            ..version("1.0-1")
+        };
+        assert_eq!(epoch.bump_epoch(), None);
+    }
+}
@@ -0,0 +1,644 @@
+//! Parsing and writing of `debian/changelog` files, as specified in the Debian Policy Manual,
+//! section 4.4.
+//!
+//! Each entry has the form:
+//!
+//! ```text
+//! package (version) distribution(s); urgency=urgency
+//!
+//!   * change details
+//!
+//!  -- maintainer name <email address>  date
+//! ```
+//!
+//! Parsing and writing a changelog reproduces the input byte for byte. Entries whose fields are
+//! modified are written in the canonical format shown above.
+
+use std::{fmt, str::FromStr};
+
+use crate::error::ChangelogError;
+use crate::DebianVersion;
+
+/// A single entry of a `debian/changelog` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangelogEntry {
+    pub package: String,
+    pub version: DebianVersion,
+    pub distributions: Vec<String>,
+    /// The `key=value` pairs following the distributions, in order. Usually only `urgency`.
+    pub metadata: Vec<(String, String)>,
+    /// The change lines between the header and the trailer, without the surrounding blank lines.
+    pub changes: Vec<String>,
+    pub maintainer: String,
+    pub email: String,
+    /// The date of the entry in RFC 2822 format, e.g. `Mon, 24 Jul 2023 10:00:00 +0200`. The
+    /// parser checks the format, but not that the day of the week matches the date.
+    pub date: String,
+    layout: Layout,
+}
+
+/// The fields of a new [`ChangelogEntry`](crate::changelog::ChangelogEntry), see
+/// [`ChangelogEntry::new`](crate::changelog::ChangelogEntry::new).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryFields {
+    pub package: String,
+    pub version: DebianVersion,
+    pub distributions: Vec<String>,
+    pub metadata: Vec<(String, String)>,
+    pub changes: Vec<String>,
+    pub maintainer: String,
+    pub email: String,
+    pub date: String,
+}
+
+/// Details of the original formatting of an entry, which are needed to reproduce it exactly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Layout {
+    /// The original header line and its canonical form at the time of parsing.
+    header: Option<(String, String)>,
+    /// The original trailer line and its canonical form at the time of parsing.
+    trailer: Option<(String, String)>,
+    /// Blank lines as they appear in the input, which may contain whitespace.
+    blank_lines_before_changes: Vec<String>,
+    blank_lines_after_changes: Vec<String>,
+    blank_lines_after_trailer: Vec<String>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            header: None,
+            trailer: None,
+            blank_lines_before_changes: vec![String::new()],
+            blank_lines_after_changes: vec![String::new()],
+            blank_lines_after_trailer: vec![String::new()],
+        }
+    }
+}
+
+impl ChangelogEntry {
+    /// Creates a new entry, which is written in the canonical format.
+    pub fn new(fields: EntryFields) -> Self {
+        let EntryFields {
+            package,
+            version,
+            distributions,
+            metadata,
+            changes,
+            maintainer,
+            email,
+            date,
+        } = fields;
+
+        Self {
+            package,
+            version,
+            distributions,
+            metadata,
+            changes,
+            maintainer,
+            email,
+            date,
+            layout: Layout::default(),
+        }
+    }
+
+    /// Returns the value of the `urgency` field of the entry.
+    pub fn urgency(&self) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("urgency"))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the header line in canonical format.
+    pub fn header(&self) -> String {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>();
+
+        format!(
+            "{} ({}) {}; {}",
+            self.package,
+            self.version,
+            self.distributions.join(" "),
+            metadata.join(", ")
+        )
+    }
+
+    /// Returns the trailer line in canonical format.
+    pub fn trailer(&self) -> String {
+        format!(" -- {} <{}>  {}", self.maintainer, self.email, self.date)
+    }
+
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        lines.push(preserved(&self.layout.header, self.header()));
+        lines.extend(self.layout.blank_lines_before_changes.iter().cloned());
+        lines.extend(self.changes.iter().cloned());
+        lines.extend(self.layout.blank_lines_after_changes.iter().cloned());
+        lines.push(preserved(&self.layout.trailer, self.trailer()));
+    }
+}
+
+/// Returns the original line if the entry still renders to the same canonical line as when it
+/// was parsed, and the new canonical line otherwise.
+fn preserved(original: &Option<(String, String)>, canonical: String) -> String {
+    match original {
+        Some((raw, parsed)) if *parsed == canonical => raw.clone(),
+        _ => canonical,
+    }
+}
+
+/// A complete `debian/changelog` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Changelog {
+    /// The entries, newest first.
+    pub entries: Vec<ChangelogEntry>,
+    /// Lines following the last entry that are not part of an entry, such as Emacs local
+    /// variables or an `Old Changelog:` section.
+    pub epilogue: Vec<String>,
+    /// Blank lines before the first entry.
+    leading_blank_lines: Vec<String>,
+    /// Blank lines after the last entry, if there is no epilogue.
+    trailing_blank_lines: Vec<String>,
+    final_newline: bool,
+}
+
+impl Default for Changelog {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            epilogue: Vec::new(),
+            leading_blank_lines: Vec::new(),
+            trailing_blank_lines: Vec::new(),
+            final_newline: true,
+        }
+    }
+}
+
+impl Changelog {
+    /// Returns the newest entry.
+    #[inline]
+    pub fn latest(&self) -> Option<&ChangelogEntry> {
+        self.entries.first()
+    }
+
+    /// Returns the version of the newest entry.
+    #[inline]
+    pub fn latest_version(&self) -> Option<&DebianVersion> {
+        self.latest().map(|entry| &entry.version)
+    }
+}
+
+impl fmt::Display for Changelog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = self.leading_blank_lines.clone();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            entry.write_lines(&mut lines);
+
+            // The blank lines after the last entry only matter if something follows.
+            if i + 1 < self.entries.len() || !self.epilogue.is_empty() {
+                lines.extend(entry.layout.blank_lines_after_trailer.iter().cloned());
+            }
+        }
+        lines.extend(self.epilogue.iter().cloned());
+        if self.epilogue.is_empty() {
+            lines.extend(self.trailing_blank_lines.iter().cloned());
+        }
+
+        write!(f, "{}", lines.join("\n"))?;
+        if self.final_newline && !lines.is_empty() {
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Changelog {
+    type Err = ChangelogError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let final_newline = s.ends_with('\n');
+        let content = s.strip_suffix('\n').unwrap_or(s);
+        if content.trim().is_empty() {
+            return Err(ChangelogError::Empty);
+        }
+
+        let lines = content.split('\n').collect::<Vec<_>>();
+        let mut entries = Vec::new();
+        let mut epilogue = Vec::new();
+        let mut trailing_blank_lines = Vec::new();
+        let leading_blank_lines = blank_lines(&lines);
+        let mut i = leading_blank_lines.len();
+
+        while i < lines.len() {
+            // Anything that isn't an entry after the first one belongs to the epilogue.
+            if !entries.is_empty() && !looks_like_header(lines[i]) {
+                epilogue = lines[i..].iter().map(|line| line.to_string()).collect();
+                break;
+            }
+
+            let header_line = i + 1;
+            let header = parse_header(lines[i], header_line)?;
+            i += 1;
+
+            let body_start = i;
+            while i < lines.len() && !lines[i].starts_with(" -- ") {
+                if looks_like_header(lines[i]) {
+                    return Err(ChangelogError::MissingTrailer(header_line));
+                }
+                i += 1;
+            }
+            if i == lines.len() {
+                return Err(ChangelogError::MissingTrailer(header_line));
+            }
+
+            let body = &lines[body_start..i];
+            let before = blank_lines(body);
+            let changes_end = body.len()
+                - body[before.len()..]
+                    .iter()
+                    .rev()
+                    .take_while(|line| is_blank(line))
+                    .count();
+            let changes = &body[before.len()..changes_end];
+            let after = blank_lines(&body[changes_end..]);
+
+            let (maintainer, email, date) = parse_trailer(lines[i], i + 1)?;
+            let trailer_line = lines[i];
+            i += 1;
+
+            let mut blank_lines_after_trailer = blank_lines(&lines[i..]);
+            i += blank_lines_after_trailer.len();
+
+            // Blank lines at the end of the file belong to the changelog, so that they stay at
+            // the end when entries are added.
+            if i == lines.len() {
+                trailing_blank_lines = blank_lines_after_trailer;
+                blank_lines_after_trailer = Layout::default().blank_lines_after_trailer;
+            }
+
+            let mut entry = ChangelogEntry::new(EntryFields {
+                package: header.package,
+                version: header.version,
+                distributions: header.distributions,
+                metadata: header.metadata,
+                changes: changes.iter().map(|line| line.to_string()).collect(),
+                maintainer,
+                email,
+                date,
+            });
+            entry.layout = Layout {
+                header: Some((lines[header_line - 1].to_string(), entry.header())),
+                trailer: Some((trailer_line.to_string(), entry.trailer())),
+                blank_lines_before_changes: before,
+                blank_lines_after_changes: after,
+                blank_lines_after_trailer,
+            };
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            entries,
+            epilogue,
+            leading_blank_lines,
+            trailing_blank_lines,
+            final_newline,
+        })
+    }
+}
+
+/// Lines containing only whitespace are blank.
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Returns the blank lines at the start of `lines`.
+fn blank_lines(lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .take_while(|line| is_blank(line))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+struct Header {
+    package: String,
+    version: DebianVersion,
+    distributions: Vec<String>,
+    metadata: Vec<(String, String)>,
+}
+
+/// Header lines start with a package name directly followed by a parenthesized version.
+fn looks_like_header(line: &str) -> bool {
+    line.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && line
+            .split_once(' ')
+            .is_some_and(|(_, rest)| rest.starts_with('('))
+}
+
+fn parse_header(line: &str, number: usize) -> Result<Header, ChangelogError> {
+    let (package, rest) = line
+        .split_once(" (")
+        .ok_or(ChangelogError::InvalidHeader(number))?;
+    let (version, rest) = rest
+        .split_once(')')
+        .ok_or(ChangelogError::InvalidHeader(number))?;
+    let (distributions, metadata) = rest
+        .split_once(';')
+        .ok_or(ChangelogError::InvalidHeader(number))?;
+
+    if package.is_empty()
+        || !package
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+    {
+        return Err(ChangelogError::InvalidHeader(number));
+    }
+
+    let version = version
+        .parse()
+        .map_err(|error| ChangelogError::Version(number, error))?;
+
+    let distributions = distributions
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if distributions.is_empty() {
+        return Err(ChangelogError::InvalidHeader(number));
+    }
+
+    let metadata = metadata
+        .split(',')
+        .map(|pair| {
+            pair.trim()
+                .split_once('=')
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+                .ok_or(ChangelogError::InvalidMetadata(number))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Header {
+        package: package.to_string(),
+        version,
+        distributions,
+        metadata,
+    })
+}
+
+fn parse_trailer(line: &str, number: usize) -> Result<(String, String, String), ChangelogError> {
+    let rest = line
+        .strip_prefix(" -- ")
+        .ok_or(ChangelogError::InvalidTrailer(number))?;
+    let (maintainer, rest) = rest
+        .split_once('<')
+        .ok_or(ChangelogError::InvalidTrailer(number))?;
+    let (email, date) = rest
+        .split_once('>')
+        .ok_or(ChangelogError::InvalidTrailer(number))?;
+
+    let (maintainer, date) = (maintainer.trim(), date.trim());
+    if maintainer.is_empty() || email.is_empty() || date.is_empty() {
+        return Err(ChangelogError::InvalidTrailer(number));
+    }
+    if !is_rfc2822_date(date) {
+        return Err(ChangelogError::InvalidDate(number));
+    }
+
+    Ok((maintainer.to_string(), email.to_string(), date.to_string()))
+}
+
+/// Checks that `date` has the form `[Mon, ]24 Jul 2023 10:00[:00] +0200` of RFC 2822, section
+/// 3.3, optionally followed by a comment such as `(CEST)`.
+fn is_rfc2822_date(date: &str) -> bool {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let number = |s: &str, digits: std::ops::RangeInclusive<usize>, max: u32| {
+        digits.contains(&s.len())
+            && s.bytes().all(|b| b.is_ascii_digit())
+            && s.parse::<u32>().is_ok_and(|n| n <= max)
+    };
+
+    let date = match date.split_once(',') {
+        Some((weekday, rest)) if WEEKDAYS.contains(&weekday.trim()) => rest,
+        Some(_) => return false,
+        None => date,
+    };
+    let mut parts = date.split_whitespace();
+    let (Some(day), Some(month), Some(year), Some(time), Some(zone)) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return false;
+    };
+    let comment = parts.collect::<Vec<_>>().join(" ");
+    if !(comment.is_empty() || comment.starts_with('(') && comment.ends_with(')')) {
+        return false;
+    }
+
+    let time = time.split(':').collect::<Vec<_>>();
+    let time_is_valid = match time[..] {
+        [hour, minute] => number(hour, 2..=2, 23) && number(minute, 2..=2, 59),
+        // 60 allows for leap seconds.
+        [hour, minute, second] => {
+            number(hour, 2..=2, 23) && number(minute, 2..=2, 59) && number(second, 2..=2, 60)
+        }
+        _ => false,
+    };
+    let zone_is_valid = zone
+        .strip_prefix(['+', '-'])
+        .is_some_and(|offset| number(offset, 4..=4, 9959) && number(&offset[2..], 2..=2, 59));
+
+    number(day, 1..=2, 31)
+        && day != "0"
+        && day != "00"
+        && MONTHS.contains(&month)
+        && number(year, 4..=4, 9999)
+        && time_is_valid
+        && zone_is_valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CHANGELOG: &str = "\
+versian (0.2.0-1) unstable; urgency=medium
+
+  * New upstream release.
+    - Native version ordering.
+
+ -- Jane Doe <jane@example.org>  Mon, 24 Jul 2023 10:00:00 +0200
+
+versian (1:0.1.0-1.1) unstable  experimental;urgency=low, binary-only=yes
+
+
+  * Non-maintainer upload.
+ -- John Doe <john@example.org> Sun, 23 Jul 2023 09:00:00 +0000
+
+
+Local variables:
+mode: debian-changelog
+End:
+";
+
+    #[test]
+    fn parse() {
+        let changelog = CHANGELOG.parse::<Changelog>().unwrap();
+
+        assert_eq!(changelog.entries.len(), 2);
+        assert_eq!(changelog.latest_version().unwrap().to_string(), "0.2.0-1");
+
+        let latest = changelog.latest().unwrap();
+        assert_eq!(latest.package, "versian");
+        assert_eq!(latest.distributions, vec!["unstable".to_string()]);
+        assert_eq!(latest.urgency(), Some("medium"));
+        assert_eq!(latest.changes.len(), 2);
+        assert_eq!(latest.maintainer, "Jane Doe");
+        assert_eq!(latest.email, "jane@example.org");
+        assert_eq!(latest.date, "Mon, 24 Jul 2023 10:00:00 +0200");
+
+        let nmu = &changelog.entries[1];
+        assert_eq!(nmu.version.epoch, Some(1));
+        assert_eq!(nmu.distributions.len(), 2);
+        assert_eq!(
+            nmu.metadata[1],
+            ("binary-only".to_string(), "yes".to_string())
+        );
+        assert_eq!(changelog.epilogue.len(), 3);
+    }
+
+    #[test]
+    fn round_trip() {
+        let changelog = CHANGELOG.parse::<Changelog>().unwrap();
+        assert_eq!(changelog.to_string(), CHANGELOG);
+
+        let without_epilogue = CHANGELOG.split("Local").next().unwrap();
+        assert_eq!(
+            without_epilogue.parse::<Changelog>().unwrap().to_string(),
+            without_epilogue
+        );
+
+        let without_newline = without_epilogue.trim_end();
+        assert_eq!(
+            without_newline.parse::<Changelog>().unwrap().to_string(),
+            without_newline
+        );
+    }
+
+    #[test]
+    fn modified_entries_are_canonical() {
+        let mut changelog = CHANGELOG.parse::<Changelog>().unwrap();
+        changelog.entries[1].version = "1:0.1.0-2".parse().unwrap();
+
+        let written = changelog.to_string();
+        assert!(written.contains(
+            "\nversian (1:0.1.0-2) unstable experimental; urgency=low, binary-only=yes\n"
+        ));
+        // The trailer hasn't changed and keeps its original formatting.
+        assert!(
+            written.contains(" -- John Doe <john@example.org> Sun, 23 Jul 2023 09:00:00 +0000\n")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!("\n".parse::<Changelog>(), Err(ChangelogError::Empty));
+        assert_eq!(
+            "versian 0.1 unstable; urgency=low\n".parse::<Changelog>(),
+            Err(ChangelogError::InvalidHeader(1))
+        );
+        assert_eq!(
+            "versian (0.1) unstable; urgency\n".parse::<Changelog>(),
+            Err(ChangelogError::InvalidMetadata(1))
+        );
+        assert_eq!(
+            "versian (0.1) unstable; urgency=low\n\n  * Foo.\n".parse::<Changelog>(),
+            Err(ChangelogError::MissingTrailer(1))
+        );
+        assert_eq!(
+            "versian (0.1) unstable; urgency=low\n\n -- Jane Doe  Mon, 24 Jul 2023 10:00:00 +0200\n"
+                .parse::<Changelog>(),
+            Err(ChangelogError::InvalidTrailer(3))
+        );
+        assert_eq!(
+            "versian (0.1) unstable; urgency=low\n\n -- Jane Doe <jane@example.org>  yesterday\n"
+                .parse::<Changelog>(),
+            Err(ChangelogError::InvalidDate(3))
+        );
+        assert!(matches!(
+            "versian (a0.1) unstable; urgency=low\n".parse::<Changelog>(),
+            Err(ChangelogError::Version(1, _))
+        ));
+    }
+
+    #[test]
+    fn leading_blank_lines() {
+        let changelog = format!("\n\n{}", CHANGELOG);
+        let parsed = changelog.parse::<Changelog>().unwrap();
+
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.to_string(), changelog);
+    }
+
+    #[test]
+    fn whitespace_only_blank_lines() {
+        let changelog = CHANGELOG
+            .split("Local")
+            .next()
+            .unwrap()
+            .replace("+0200\n\n", "+0200\n  \n")
+            .replace("medium\n\n", "medium\n\t\n");
+        let parsed = changelog.parse::<Changelog>().unwrap();
+
+        assert_eq!(parsed.entries.len(), 2);
+        assert!(parsed.epilogue.is_empty());
+        assert_eq!(parsed.latest().unwrap().changes.len(), 2);
+        assert_eq!(parsed.to_string(), changelog);
+        assert_eq!(
+            format!(" \n{}", changelog)
+                .parse::<Changelog>()
+                .unwrap()
+                .to_string(),
+            format!(" \n{}", changelog)
+        );
+    }
+
+    #[test]
+    fn dates() {
+        for date in [
+            "Mon, 24 Jul 2023 10:00:00 +0200",
+            "24 Jul 2023 10:00 -0000",
+            "Sun,  3 Sep 2023 23:59:60 +0530 (IST)",
+        ] {
+            assert!(is_rfc2822_date(date), "{}", date);
+        }
+        for date in [
+            "",
+            "2023-07-24 10:00:00 +0200",
+            "Mon, 24 Jul 2023 10:00:00",
+            "Monday, 24 Jul 2023 10:00:00 +0200",
+            "Mon, 32 Jul 2023 10:00:00 +0200",
+            "Mon, 24 July 2023 10:00:00 +0200",
+            "Mon, 24 Jul 23 10:00:00 +0200",
+            "Mon, 24 Jul 2023 24:00:00 +0200",
+            "Mon, 24 Jul 2023 10:00:00 CEST",
+            "Mon, 24 Jul 2023 10:00:00 +0260",
+            "Mon, 24 Jul 2023 10:00:00 +0200 CEST",
+        ] {
+            assert!(!is_rfc2822_date(date), "{}", date);
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::cmp::Ordering;
+
+use crate::{DebianVersion, DebianVersionRef};
+
+/// Compares two [`DebianVersion`](crate::DebianVersion)s as specified in the Debian Policy Manual,
+/// section 5.6.12: epochs are compared numerically (a missing epoch counts as `0`), then the
+/// upstream versions and finally the Debian revisions are compared with
+/// [`compare_fragment`](crate::compare::compare_fragment). A missing Debian revision compares
+/// like an empty one.
+pub fn compare_versions(a: &DebianVersion, b: &DebianVersion) -> Ordering {
+    a.epoch
+        .unwrap_or_default()
+        .cmp(&b.epoch.unwrap_or_default())
+        .then_with(|| compare_fragment(&a.upstream_version, &b.upstream_version))
+        .then_with(|| {
+            compare_fragment(
+                a.debian_revision.as_deref().unwrap_or_default(),
+                b.debian_revision.as_deref().unwrap_or_default(),
+            )
+        })
+}
+
+/// Compares two [`DebianVersionRef`](crate::DebianVersionRef)s, see
+/// [`compare_versions`](crate::compare::compare_versions).
+pub fn compare_version_refs(a: &DebianVersionRef<'_>, b: &DebianVersionRef<'_>) -> Ordering {
+    a.epoch()
+        .unwrap_or_default()
+        .cmp(&b.epoch().unwrap_or_default())
+        .then_with(|| compare_fragment(a.upstream_version, b.upstream_version))
+        .then_with(|| {
+            compare_fragment(
+                a.debian_revision.unwrap_or_default(),
+                b.debian_revision.unwrap_or_default(),
+            )
+        })
+}
+
+/// Compares an upstream version or Debian revision string the way dpkg's `verrevcmp` does.
+///
+/// Both strings are split into alternating runs of non-digits and digits. Non-digit runs are
+/// compared character by character, where `~` sorts before everything (even the end of the
+/// run), letters sort before all other characters, and digit runs are compared numerically.
+pub fn compare_fragment(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        // Non-digit prefix.
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ac = order(a.get(i).copied());
+            let bc = order(b.get(j).copied());
+
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+
+            i += 1;
+            j += 1;
+        }
+
+        // Numeric part, leading zeros are insignificant.
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+
+        let mut first_diff = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+
+            i += 1;
+            j += 1;
+        }
+
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Weight of a single character inside a non-digit run. `None` stands for the end of the run.
+fn order(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => i32::from(c),
+        Some(b'~') => -1,
+        Some(c) => i32::from(c) + 256,
+    }
+}
+
+/// Marks the end of a non-digit run in a sort key. Sorts after `~` and before all other
+/// characters, like the end of a run does in [`compare_fragment`](crate::compare::compare_fragment).
+const END_OF_RUN: u8 = 2;
+
+impl DebianVersion {
+    /// Returns a byte string whose lexicographic order is the order of the versions, so that
+    /// versions can be sorted with `memcmp` or stored in database indexes. Versions comparing
+    /// equal, e.g. `1.01` and `1.1`, have equal keys. The format of the key is not stable across
+    /// releases of this crate.
+    pub fn sort_key(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.upstream_version.len() * 2 + 8);
+
+        push_number(
+            &mut key,
+            self.epoch.unwrap_or_default().to_string().as_bytes(),
+        );
+        push_fragment(&mut key, &self.upstream_version);
+        push_fragment(
+            &mut key,
+            self.debian_revision.as_deref().unwrap_or_default(),
+        );
+
+        key
+    }
+}
+
+impl DebianVersionRef<'_> {
+    /// Returns the sort key of the version, see
+    /// [`DebianVersion::sort_key`](crate::DebianVersion::sort_key).
+    pub fn sort_key(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.upstream_version.len() * 2 + 8);
+
+        push_number(&mut key, self.epoch.unwrap_or_default().as_bytes());
+        push_fragment(&mut key, self.upstream_version);
+        push_fragment(&mut key, self.debian_revision.unwrap_or_default());
+
+        key
+    }
+}
+
+/// Appends the key of an upstream version or Debian revision: every non-digit run followed by
+/// [`END_OF_RUN`] and the following digit run, terminated by another `END_OF_RUN`. The first pair
+/// of runs is always written, even if both are empty.
+fn push_fragment(key: &mut Vec<u8>, s: &str) {
+    let s = s.as_bytes();
+    let mut i = 0;
+
+    loop {
+        while i < s.len() && !s[i].is_ascii_digit() {
+            match s[i] {
+                b'~' => key.push(1),
+                c if c.is_ascii_alphabetic() => key.push(c),
+                // All other characters sort after letters, see `order`.
+                c if c < 0x7f => key.push(0x80 + c),
+                c => key.extend([0xff, c]),
+            }
+            i += 1;
+        }
+        key.push(END_OF_RUN);
+
+        let start = i;
+        while i < s.len() && s[i].is_ascii_digit() {
+            i += 1;
+        }
+        push_number(key, &s[start..i]);
+
+        if i == s.len() {
+            break;
+        }
+    }
+
+    key.push(END_OF_RUN);
+}
+
+/// Appends a run of digits as its length without leading zeros followed by the significant
+/// digits, so that longer numbers sort after shorter ones.
+fn push_number(key: &mut Vec<u8>, digits: &[u8]) {
+    let start = digits
+        .iter()
+        .position(|&digit| digit != b'0')
+        .unwrap_or(digits.len());
+    let digits = &digits[start..];
+
+    match u8::try_from(digits.len()) {
+        Ok(len) if len < u8::MAX => key.push(len),
+        _ => {
+            key.push(u8::MAX);
+            key.extend((digits.len() as u64).to_be_bytes());
+        }
+    }
+    key.extend(digits);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_version;
+    use pretty_assertions::assert_eq;
+
+    /// Small SplitMix64 generator, so runs are reproducible from the seed alone.
+    pub(super) struct SplitMix64(pub(super) u64);
+
+    impl SplitMix64 {
+        pub(super) fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        pub(super) fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn pick(&mut self, chars: &[u8]) -> char {
+            char::from(chars[self.below(chars.len())])
+        }
+    }
+
+    const DIGITS: &[u8] = b"0123456789";
+    // Biased towards the characters that matter for ordering: separators, `~`, `+` and a few
+    // letters, so that generated versions frequently share prefixes.
+    const FRAGMENT: &[u8] = b"0123456789001.....~~~+++abzABZ";
+
+    fn fragment(rng: &mut SplitMix64, extra: &[u8]) -> String {
+        let mut s = String::new();
+        s.push(rng.pick(DIGITS));
+
+        for _ in 0..rng.below(8) {
+            if !extra.is_empty() && rng.below(10) == 0 {
+                s.push(rng.pick(extra));
+            } else {
+                s.push(rng.pick(FRAGMENT));
+            }
+        }
+
+        s
+    }
+
+    /// Generates a random valid version.
+    pub(super) fn version(rng: &mut SplitMix64) -> String {
+        let epoch = (rng.below(4) == 0).then(|| rng.below(3));
+        let has_revision = rng.below(3) != 0;
+
+        // Only valid characters for the given shape, see `is_valid_char` and
+        // `ValidateUpstreamVersion`.
+        let mut extra = Vec::new();
+        if epoch.is_some() {
+            extra.push(b':');
+        }
+        if has_revision {
+            extra.push(b'-');
+        }
+
+        let mut s = String::new();
+        if let Some(epoch) = epoch {
+            s.push_str(&format!("{}:", epoch));
+        }
+        s.push_str(&fragment(rng, &extra));
+        if has_revision {
+            s.push('-');
+            s.push_str(&fragment(rng, &[]));
+        }
+
+        s
+    }
+
+    #[test]
+    fn sort_key_agrees_with_compare() {
+        let mut rng = SplitMix64(0x2545_f491_4f6c_dd1d);
+        let versions = (0..2_000)
+            .map(|_| version(&mut rng).parse::<DebianVersion>().unwrap())
+            .collect::<Vec<_>>();
+
+        for pair in versions.windows(2) {
+            assert_eq!(
+                pair[0].sort_key().cmp(&pair[1].sort_key()),
+                compare_versions(&pair[0], &pair[1]),
+                "{} <=> {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        let mut sorted = versions.clone();
+        sorted.sort();
+        let mut by_key = versions;
+        by_key.sort_by_key(DebianVersion::sort_key);
+        assert!(sorted
+            .iter()
+            .zip(&by_key)
+            .all(|(a, b)| compare_versions(a, b) == Ordering::Equal));
+    }
+
+    #[test]
+    fn sort_key_edge_cases() {
+        let key = |s: &str| s.parse::<DebianVersion>().unwrap().sort_key();
+
+        assert_eq!(key("1.01"), key("1.1"));
+        assert_eq!(key("0:1.0"), key("1.0-0"));
+        assert!(key("1.0~rc1") < key("1.0"));
+        assert!(key("1.0") < key("1.0a"));
+        assert!(key("1.0a") < key("1.0+"));
+        assert!(key("1.9") < key("1.10"));
+        assert!(key("9:1") < key("10:0"));
+        assert!(key(&format!("1.{}", "9".repeat(300))) > key(&format!("1.{}", "9".repeat(299))));
+
+        let parsed = parse_version("1:2.0~b-3").unwrap();
+        assert_eq!(parsed.sort_key(), key("1:2.0~b-3"));
+    }
+}
+
+/// Differential tests checking the native ordering against libapt-pkg on randomly generated
+/// versions.
+#[cfg(all(test, feature = "cmp"))]
+mod apt_differential {
+    use super::tests::{version, SplitMix64};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Number of random versions to generate per run.
+    const VERSIONS: usize = 20_000;
+    /// Number of partners each version is compared against.
+    const PARTNERS: usize = 8;
+    /// Seed of the random versions, unless overridden by the `VERSIAN_APT_SEED` environment
+    /// variable.
+    const DEFAULT_SEED: u64 = 0x0a97_5eed;
+
+    #[test]
+    fn native_agrees_with_apt() {
+        // Set `VERSIAN_APT_SEED` to reproduce a failure or to explore other versions.
+        let seed = std::env::var("VERSIAN_APT_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(DEFAULT_SEED);
+        let mut rng = SplitMix64(seed);
+
+        let versions = (0..VERSIONS)
+            .map(|_| version(&mut rng))
+            .map(|s| {
+                let parsed = s.parse::<DebianVersion>();
+                assert!(parsed.is_ok(), "seed {}: failed to parse {:?}", seed, s);
+                parsed.unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        for a in &versions {
+            for _ in 0..PARTNERS {
+                let b = &versions[rng.below(versions.len())];
+
+                assert_eq!(
+                    compare_versions(a, b),
+                    a.cmp_with_apt(b),
+                    "seed {}: {} vs {}",
+                    seed,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn native_agrees_with_apt_on_sorted_corpus() {
+        let mut rng = SplitMix64(0x5eed);
+        let mut versions = (0..VERSIONS)
+            .map(|_| version(&mut rng).parse::<DebianVersion>().unwrap())
+            .collect::<Vec<_>>();
+
+        versions.sort();
+
+        // Adjacent pairs of a sorted list are the hardest cases, they only differ in the details.
+        for pair in versions.windows(2) {
+            assert_eq!(
+                compare_versions(&pair[0], &pair[1]),
+                pair[0].cmp_with_apt(&pair[1]),
+                "{} vs {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+}
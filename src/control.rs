@@ -0,0 +1,287 @@
+//! Typed views of `debian/control` files on top of the [`deb822`](crate::deb822) parser.
+//!
+//! The first paragraph of a `debian/control` file describes the source package, every following
+//! paragraph a binary package. The views borrow the underlying
+//! [`Paragraph`](crate::deb822::Paragraph)s and parse versions and relationship fields on access.
+
+use std::{fmt, str::FromStr};
+
+use crate::deb822::{Deb822, Paragraph};
+use crate::error::Deb822Error;
+use crate::relation::RelationField;
+use crate::DebianVersion;
+
+/// A `debian/control` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Control {
+    pub deb822: Deb822,
+}
+
+impl Control {
+    /// Returns the source stanza, the first paragraph of the file.
+    pub fn source(&self) -> Option<SourceStanza<'_>> {
+        self.deb822.paragraphs.first().map(SourceStanza)
+    }
+
+    /// Returns the binary stanzas, all paragraphs following the source stanza.
+    pub fn binaries(&self) -> impl Iterator<Item = BinaryStanza<'_>> {
+        self.deb822.paragraphs.iter().skip(1).map(BinaryStanza)
+    }
+
+    /// Returns the binary stanza of the package called `package`.
+    pub fn binary(&self, package: &str) -> Option<BinaryStanza<'_>> {
+        self.binaries()
+            .find(|binary| binary.0.get("Package").as_deref() == Some(package))
+    }
+}
+
+impl fmt::Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.deb822)
+    }
+}
+
+impl FromStr for Control {
+    type Err = Deb822Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self { deb822: s.parse()? })
+    }
+}
+
+/// The source stanza of a `debian/control` file.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceStanza<'a>(pub &'a Paragraph);
+
+impl SourceStanza<'_> {
+    pub fn source(&self) -> Result<String, Deb822Error> {
+        required(self.0, "Source")
+    }
+
+    pub fn maintainer(&self) -> Option<String> {
+        self.0.get("Maintainer")
+    }
+
+    pub fn standards_version(&self) -> Option<String> {
+        self.0.get("Standards-Version")
+    }
+
+    pub fn build_depends(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Build-Depends")
+    }
+
+    pub fn build_depends_indep(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Build-Depends-Indep")
+    }
+
+    pub fn build_depends_arch(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Build-Depends-Arch")
+    }
+
+    pub fn build_conflicts(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Build-Conflicts")
+    }
+}
+
+/// A binary stanza of a `debian/control` file.
+#[derive(Clone, Copy, Debug)]
+pub struct BinaryStanza<'a>(pub &'a Paragraph);
+
+impl BinaryStanza<'_> {
+    pub fn package(&self) -> Result<String, Deb822Error> {
+        required(self.0, "Package")
+    }
+
+    pub fn architecture(&self) -> Option<String> {
+        self.0.get("Architecture")
+    }
+
+    /// Returns the version of the package. Binary stanzas usually inherit the version from the
+    /// changelog and have no `Version` field.
+    pub fn version(&self) -> Result<Option<DebianVersion>, Deb822Error> {
+        version(self.0)
+    }
+
+    pub fn depends(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Depends")
+    }
+
+    pub fn pre_depends(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Pre-Depends")
+    }
+
+    pub fn recommends(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Recommends")
+    }
+
+    pub fn suggests(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Suggests")
+    }
+
+    pub fn breaks(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Breaks")
+    }
+
+    pub fn conflicts(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Conflicts")
+    }
+
+    pub fn provides(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Provides")
+    }
+
+    pub fn replaces(&self) -> Result<RelationField, Deb822Error> {
+        relations(self.0, "Replaces")
+    }
+}
+
+pub(crate) fn required(paragraph: &Paragraph, name: &'static str) -> Result<String, Deb822Error> {
+    paragraph.get(name).ok_or(Deb822Error::MissingField(name))
+}
+
+pub(crate) fn version(paragraph: &Paragraph) -> Result<Option<DebianVersion>, Deb822Error> {
+    Ok(paragraph
+        .get("Version")
+        .map(|version| version.parse())
+        .transpose()?)
+}
+
+/// Parses the relationship field called `name`, which is empty if the field is missing.
+///
+/// Relations containing substitution variables, such as `${misc:Depends}` or
+/// `foo (= ${binary:Version})`, are skipped, since they are only known at build time. A relation
+/// is skipped as a whole if any of its alternatives contains one, as dropping just that
+/// alternative would make the relation stricter, so both `${shlibs:Depends}, bar` and
+/// `${x} | y, bar` yield `bar`.
+pub(crate) fn relations(paragraph: &Paragraph, name: &str) -> Result<RelationField, Deb822Error> {
+    let value = match paragraph.get(name) {
+        Some(value) => value,
+        None => return Ok(RelationField::default()),
+    };
+
+    Ok(RelationField {
+        relations: value
+            .split(',')
+            .filter(|relation| !relation.trim().is_empty() && !has_substvar(relation))
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?,
+    })
+}
+
+fn has_substvar(s: &str) -> bool {
+    s.contains("${")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RelationError;
+    use pretty_assertions::assert_eq;
+
+    const CONTROL: &str = "\
+Source: versian
+Maintainer: Jane Doe <jane@example.org>
+Build-Depends: debhelper-compat (= 13),
+               cargo,
+               librust-serde-dev (>= 1.0) <!nocheck>
+Standards-Version: 4.6.2
+
+Package: versian
+Architecture: any
+Depends: ${misc:Depends}, ${shlibs:Depends}, libc6 (>= 2.34) | musl
+Description: Debian version parser
+
+Package: versian-doc
+Architecture: all
+Version: 1:0.2.0-1
+Description: Debian version parser (documentation)
+";
+
+    #[test]
+    fn source_stanza() {
+        let control = CONTROL.parse::<Control>().unwrap();
+        let source = control.source().unwrap();
+
+        assert_eq!(source.source().unwrap(), "versian");
+        assert_eq!(source.standards_version().as_deref(), Some("4.6.2"));
+        assert_eq!(
+            source.build_depends().unwrap().to_string(),
+            "debhelper-compat (= 13), cargo, librust-serde-dev (>= 1.0) <!nocheck>"
+        );
+        assert!(source.build_conflicts().unwrap().relations.is_empty());
+        assert_eq!(control.to_string(), CONTROL);
+    }
+
+    #[test]
+    fn binary_stanzas() {
+        let control = CONTROL.parse::<Control>().unwrap();
+        let packages = control
+            .binaries()
+            .map(|binary| binary.package().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(packages, vec!["versian", "versian-doc"]);
+
+        let versian = control.binary("versian").unwrap();
+        assert_eq!(
+            versian.depends().unwrap().to_string(),
+            "libc6 (>= 2.34) | musl"
+        );
+        assert_eq!(versian.version().unwrap(), None);
+
+        let doc = control.binary("versian-doc").unwrap();
+        assert_eq!(doc.version().unwrap(), Some("1:0.2.0-1".parse().unwrap()));
+    }
+
+    #[test]
+    fn substvars() {
+        // Based on the binary stanzas of the `libxml2` source package.
+        let control = "\
+Source: libxml2
+
+Package: libxml2-utils
+Architecture: any
+Depends: libxml2 (>= ${binary:Version}), ${misc:Depends}, ${shlibs:Depends}
+Description: XML utilities
+
+Package: libxml2-dev
+Architecture: any
+Depends: libxml2 (= ${binary:Version}), libicu-dev, ${misc:Depends}, zlib1g-dev | libz-dev
+Recommends: ${x} | pkgconf, ${perl:Depends}, libxml2-utils
+Breaks: libxml2-utils (<< ${source:Version}) [!hurd-i386], libxml2-doc (<< 2.9)
+Description: Development files for the GNOME XML library
+"
+        .parse::<Control>()
+        .unwrap();
+
+        let utils = control.binary("libxml2-utils").unwrap();
+        assert!(utils.depends().unwrap().relations.is_empty());
+
+        let dev = control.binary("libxml2-dev").unwrap();
+        assert_eq!(
+            dev.depends().unwrap().to_string(),
+            "libicu-dev, zlib1g-dev | libz-dev"
+        );
+        // `pkgconf` is only one of the alternatives, so it isn't required on its own.
+        assert_eq!(dev.recommends().unwrap().to_string(), "libxml2-utils");
+        assert_eq!(dev.breaks().unwrap().to_string(), "libxml2-doc (<< 2.9)");
+    }
+
+    #[test]
+    fn errors() {
+        let control = "Maintainer: Jane Doe <jane@example.org>\n\nPackage: aa\nDepends: bb (>= 1\nVersion: -1\n"
+            .parse::<Control>()
+            .unwrap();
+
+        assert_eq!(
+            control.source().unwrap().source(),
+            Err(Deb822Error::MissingField("Source"))
+        );
+
+        let binary = control.binary("aa").unwrap();
+        assert_eq!(
+            binary.depends(),
+            Err(Deb822Error::Relation(RelationError::UnterminatedVersion))
+        );
+        assert!(matches!(binary.version(), Err(Deb822Error::Version(_))));
+    }
+}
@@ -0,0 +1,407 @@
+//! Generation of new `debian/changelog` entries, similar to `dch`.
+//!
+//! An [`EntryBuilder`](crate::dch::EntryBuilder) derives the version of the new entry from the
+//! newest entry of a [`Changelog`](crate::changelog::Changelog) according to the
+//! [`UploadKind`](crate::dch::UploadKind), and formats the change bullets the way `dch` does.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::changelog::{Changelog, ChangelogEntry, EntryFields};
+use crate::error::ChangelogError;
+use crate::DebianVersion;
+
+/// Maximum line length of change bullets, longer bullets are wrapped.
+const LINE_WIDTH: usize = 80;
+
+/// The kind of upload, which determines the version of a new entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UploadKind {
+    /// A new upstream release with the given upstream version, e.g. `1.2-3` → `1.3-1`.
+    NewUpstream(String),
+    /// A new maintainer upload, e.g. `1.2-3` → `1.2-4`.
+    NewRevision,
+    /// A non-maintainer upload, e.g. `1.2-3` → `1.2-3.1`.
+    NonMaintainerUpload,
+    /// An upload by a team member not listed as maintainer, e.g. `1.2-3` → `1.2-4`.
+    TeamUpload,
+}
+
+/// Builder for a new [`ChangelogEntry`](crate::changelog::ChangelogEntry).
+///
+/// The package name defaults to the one of the previous entry, the distribution to
+/// `UNRELEASED`, the urgency to `medium` and the date to the current time.
+#[derive(Clone, Debug)]
+pub struct EntryBuilder {
+    kind: UploadKind,
+    package: Option<String>,
+    version: Option<DebianVersion>,
+    distribution: String,
+    urgency: String,
+    maintainer: Option<(String, String)>,
+    changes: Vec<String>,
+    date: Option<String>,
+}
+
+impl EntryBuilder {
+    /// Creates a builder for an entry of the given kind of upload.
+    pub fn new(kind: UploadKind) -> Self {
+        Self {
+            kind,
+            package: None,
+            version: None,
+            distribution: "UNRELEASED".to_string(),
+            urgency: "medium".to_string(),
+            maintainer: None,
+            changes: Vec::new(),
+            date: None,
+        }
+    }
+
+    /// Sets the source package name. Required if there is no previous entry.
+    pub fn package(mut self, package: &str) -> Self {
+        self.package = Some(package.to_string());
+        self
+    }
+
+    /// Sets the version explicitly instead of deriving it from the previous entry. Required if
+    /// there is no previous entry.
+    pub fn version(mut self, version: DebianVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the target distribution, `UNRELEASED` by default.
+    pub fn distribution(mut self, distribution: &str) -> Self {
+        self.distribution = distribution.to_string();
+        self
+    }
+
+    /// Sets the urgency, `medium` by default.
+    pub fn urgency(mut self, urgency: &str) -> Self {
+        self.urgency = urgency.to_string();
+        self
+    }
+
+    /// Sets the maintainer. Required unless set with
+    /// [`maintainer_from_env`](crate::dch::EntryBuilder::maintainer_from_env).
+    pub fn maintainer(mut self, name: &str, email: &str) -> Self {
+        self.maintainer = Some((name.to_string(), email.to_string()));
+        self
+    }
+
+    /// Takes the maintainer from the `DEBFULLNAME` and `DEBEMAIL` environment variables, falling
+    /// back to `NAME` and `EMAIL` like `dch` does.
+    pub fn maintainer_from_env(mut self) -> Self {
+        let var = |primary: &str, fallback: &str| {
+            std::env::var(primary)
+                .or_else(|_| std::env::var(fallback))
+                .ok()
+        };
+
+        if let (Some(name), Some(email)) = (var("DEBFULLNAME", "NAME"), var("DEBEMAIL", "EMAIL")) {
+            self.maintainer = Some((name, email));
+        }
+        self
+    }
+
+    /// Adds a change bullet, given without the leading `*`.
+    pub fn change(mut self, change: &str) -> Self {
+        self.changes.push(change.to_string());
+        self
+    }
+
+    /// Sets the date in RFC 2822 format instead of using the current time.
+    pub fn date(mut self, date: &str) -> Self {
+        self.date = Some(date.to_string());
+        self
+    }
+
+    /// Builds the entry following `previous`, the newest entry of the changelog if there is one.
+    pub fn build(
+        self,
+        previous: Option<&ChangelogEntry>,
+    ) -> Result<ChangelogEntry, ChangelogError> {
+        let package = self
+            .package
+            .or_else(|| previous.map(|entry| entry.package.clone()))
+            .ok_or(ChangelogError::MissingPackage)?;
+        let (maintainer, email) = self.maintainer.ok_or(ChangelogError::MissingMaintainer)?;
+
+        let version = match (self.version, previous) {
+            (Some(version), _) => version,
+            (None, Some(previous)) => next_version(&self.kind, &previous.version)?,
+            (None, None) => return Err(ChangelogError::MissingVersion),
+        };
+        if let Some(previous) = previous {
+            if version <= previous.version {
+                return Err(ChangelogError::VersionNotNewer);
+            }
+        }
+
+        let mut bullets = match self.kind {
+            UploadKind::NewUpstream(_) => vec!["New upstream release.".to_string()],
+            UploadKind::NonMaintainerUpload => vec!["Non-maintainer upload.".to_string()],
+            UploadKind::TeamUpload => vec!["Team upload.".to_string()],
+            _ => Vec::new(),
+        };
+        bullets.extend(self.changes);
+
+        Ok(ChangelogEntry::new(EntryFields {
+            package,
+            version,
+            distributions: vec![self.distribution],
+            metadata: vec![("urgency".to_string(), self.urgency)],
+            changes: bullets
+                .iter()
+                .flat_map(|bullet| wrap_bullet(bullet))
+                .collect(),
+            maintainer,
+            email,
+            date: self.date.unwrap_or_else(now_rfc2822),
+        }))
+    }
+}
+
+impl Changelog {
+    /// Builds a new entry following the newest one and adds it to the top of the changelog.
+    pub fn prepend(&mut self, builder: EntryBuilder) -> Result<&ChangelogEntry, ChangelogError> {
+        let entry = builder.build(self.latest())?;
+        self.entries.insert(0, entry);
+
+        Ok(&self.entries[0])
+    }
+}
+
+fn next_version(
+    kind: &UploadKind,
+    previous: &DebianVersion,
+) -> Result<DebianVersion, ChangelogError> {
+    match kind {
+        UploadKind::NewUpstream(upstream_version) => previous
+            .new_upstream_version(upstream_version)
+            .map_err(ChangelogError::NewVersion),
+        UploadKind::NewRevision | UploadKind::TeamUpload if previous.debian_revision.is_none() => {
+            Err(ChangelogError::NativeVersion)
+        }
+        UploadKind::NewRevision | UploadKind::TeamUpload => previous
+            .next_revision()
+            .ok_or(ChangelogError::MissingVersion),
+        UploadKind::NonMaintainerUpload => Ok(previous.nmu()),
+    }
+}
+
+/// Formats a change as `  * change`, wrapping it at word boundaries with continuation lines
+/// indented by four spaces. The width is counted in characters.
+fn wrap_bullet(change: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::from("  *");
+    let mut width = line.len();
+    let mut line_has_words = false;
+
+    for word in change.split_whitespace() {
+        let word_width = word.chars().count();
+        if line_has_words && width + 1 + word_width > LINE_WIDTH {
+            lines.push(std::mem::replace(&mut line, String::from("   ")));
+            width = line.len();
+        }
+
+        line.push(' ');
+        line.push_str(word);
+        width += 1 + word_width;
+        line_has_words = true;
+    }
+    lines.push(line);
+
+    lines
+}
+
+/// Returns the current time in RFC 2822 format, in UTC.
+fn now_rfc2822() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    format_rfc2822(seconds)
+}
+
+/// Formats seconds since the Unix epoch as an RFC 2822 date in UTC, e.g.
+/// `Thu, 01 Jan 1970 00:00:00 +0000`.
+fn format_rfc2822(seconds: u64) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let days = seconds / 86_400;
+    let time = seconds % 86_400;
+
+    // Civil date from days since the epoch, see Howard Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DebianVersionError;
+    use pretty_assertions::assert_eq;
+
+    const CHANGELOG: &str = "\
+versian (0.2.0-1) unstable; urgency=medium
+
+  * New upstream release.
+
+ -- Jane Doe <jane@example.org>  Mon, 24 Jul 2023 10:00:00 +0200
+";
+
+    fn prepend(kind: UploadKind) -> Result<Changelog, ChangelogError> {
+        let mut changelog = CHANGELOG.parse::<Changelog>().unwrap();
+        changelog.prepend(
+            EntryBuilder::new(kind)
+                .maintainer("John Doe", "john@example.org")
+                .date("Tue, 25 Jul 2023 10:00:00 +0000")
+                .change("Fix the build."),
+        )?;
+
+        Ok(changelog)
+    }
+
+    #[test]
+    fn prepend_new_revision() {
+        let changelog = prepend(UploadKind::NewRevision).unwrap();
+
+        assert_eq!(
+            changelog.to_string(),
+            format!(
+                "\
+versian (0.2.0-2) UNRELEASED; urgency=medium
+
+  * Fix the build.
+
+ -- John Doe <john@example.org>  Tue, 25 Jul 2023 10:00:00 +0000
+
+{}",
+                CHANGELOG
+            )
+        );
+    }
+
+    #[test]
+    fn versions_by_upload_kind() {
+        let version = |kind| prepend(kind).unwrap().latest_version().unwrap().to_string();
+
+        assert_eq!(
+            version(UploadKind::NewUpstream("0.3.0".to_string())),
+            "0.3.0-1"
+        );
+        assert_eq!(version(UploadKind::NonMaintainerUpload), "0.2.0-1.1");
+        assert_eq!(version(UploadKind::TeamUpload), "0.2.0-2");
+    }
+
+    #[test]
+    fn upload_kind_bullets() {
+        let changelog = prepend(UploadKind::TeamUpload).unwrap();
+
+        assert_eq!(
+            changelog.latest().unwrap().changes,
+            vec![
+                "  * Team upload.".to_string(),
+                "  * Fix the build.".to_string()
+            ]
+        );
+
+        let changelog = prepend(UploadKind::NewUpstream("0.3.0".to_string())).unwrap();
+        assert_eq!(
+            changelog.latest().unwrap().changes,
+            vec![
+                "  * New upstream release.".to_string(),
+                "  * Fix the build.".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            prepend(UploadKind::NewUpstream("0.1.0".to_string())).unwrap_err(),
+            ChangelogError::VersionNotNewer
+        );
+        assert_eq!(
+            EntryBuilder::new(UploadKind::NewRevision)
+                .build(None)
+                .unwrap_err(),
+            ChangelogError::MissingPackage
+        );
+        assert_eq!(
+            EntryBuilder::new(UploadKind::NewRevision)
+                .package("versian")
+                .maintainer("John Doe", "john@example.org")
+                .build(None)
+                .unwrap_err(),
+            ChangelogError::MissingVersion
+        );
+        assert!(matches!(
+            prepend(UploadKind::NewUpstream("a0.3".to_string())).unwrap_err(),
+            ChangelogError::NewVersion(DebianVersionError::UpstreamStartWithDigit(_))
+        ));
+
+        let native = "versian (0.2.0) unstable; urgency=medium\n\n  * Release.\n\n -- Jane Doe <jane@example.org>  Mon, 24 Jul 2023 10:00:00 +0200\n";
+        let previous = native.parse::<Changelog>().unwrap();
+        for kind in [UploadKind::NewRevision, UploadKind::TeamUpload] {
+            assert_eq!(
+                EntryBuilder::new(kind)
+                    .maintainer("John Doe", "john@example.org")
+                    .build(previous.latest())
+                    .unwrap_err(),
+                ChangelogError::NativeVersion
+            );
+        }
+    }
+
+    #[test]
+    fn wrap_long_bullets() {
+        let lines = wrap_bullet(&"word ".repeat(30));
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= LINE_WIDTH));
+        assert!(lines[0].starts_with("  * word"));
+        assert!(lines[1].starts_with("    word"));
+
+        // Width is measured in characters, not bytes.
+        let lines = wrap_bullet(&"wörd ".repeat(15));
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].chars().count(), 78);
+    }
+
+    #[test]
+    fn rfc2822() {
+        assert_eq!(format_rfc2822(0), "Thu, 01 Jan 1970 00:00:00 +0000");
+        assert_eq!(
+            format_rfc2822(1_690_279_200),
+            "Tue, 25 Jul 2023 10:00:00 +0000"
+        );
+        assert_eq!(
+            format_rfc2822(951_782_400),
+            "Tue, 29 Feb 2000 00:00:00 +0000"
+        );
+    }
+}
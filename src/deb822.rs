@@ -0,0 +1,586 @@
+//! Parsing and writing of files in the deb822 format, see deb822(5): paragraphs of `Field: value`
+//! lines separated by blank lines, as used by `debian/control`, `.dsc` files and APT indexes.
+//!
+//! Parsing and writing a file reproduces the input byte for byte, including comments, the
+//! formatting of continuation lines and an OpenPGP cleartext signature wrapping the paragraphs.
+//! Fields changed with [`Paragraph::set`](crate::deb822::Paragraph::set) are written in the
+//! canonical format.
+
+use std::{fmt, io::BufRead, str::FromStr};
+
+use crate::error::{Deb822Error, IndexError};
+
+const BEGIN_SIGNED_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+const END_SIGNATURE: &str = "-----END PGP SIGNATURE-----";
+
+/// A single field of a [`Paragraph`](crate::deb822::Paragraph).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    name: String,
+    /// Everything following the colon, including continuation lines, exactly as in the input.
+    raw: String,
+}
+
+impl Field {
+    /// Creates a field from its logical value, see [`value`](crate::deb822::Field::value).
+    pub fn new(name: &str, value: &str) -> Self {
+        let mut lines = value.split('\n');
+        let mut raw = match lines.next() {
+            Some(first) if !first.is_empty() => format!(" {}", first),
+            _ => String::new(),
+        };
+
+        for line in lines {
+            raw.push_str("\n ");
+            raw.push_str(if line.is_empty() { "." } else { line });
+        }
+
+        Self {
+            name: name.to_string(),
+            raw,
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the logical value of the field: the first line and the continuation lines without
+    /// their leading space, separated by newlines. Continuation lines consisting of a single `.`
+    /// stand for empty lines.
+    pub fn value(&self) -> String {
+        let mut lines = self.raw.split('\n');
+        let mut value = lines.next().unwrap_or_default().trim().to_string();
+
+        // Comments may be interspersed with continuation lines, which always start with a space
+        // or a tab.
+        for line in lines.filter(|line| !line.starts_with('#')) {
+            let line = line[1..].trim_end();
+            value.push('\n');
+            if line != "." {
+                value.push_str(line);
+            }
+        }
+
+        value.trim_start().to_string()
+    }
+}
+
+/// An entry of a [`Paragraph`](crate::deb822::Paragraph).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Entry {
+    Field(Field),
+    /// A comment line, including the leading `#`.
+    Comment(String),
+}
+
+/// A paragraph (also called stanza) of a deb822 file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Paragraph {
+    /// The blank lines separating the paragraph from the previous one.
+    separator: Vec<String>,
+    pub entries: Vec<Entry>,
+}
+
+impl Paragraph {
+    /// Returns the fields of the paragraph in order, skipping comments.
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Field(field) => Some(field),
+            Entry::Comment(_) => None,
+        })
+    }
+
+    /// Returns the field called `name`. Field names are case-insensitive.
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the logical value of the field called `name`.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.field(name).map(Field::value)
+    }
+
+    /// Returns `true` if the paragraph contains the field called `name`.
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.field(name).is_some()
+    }
+
+    /// Sets the value of the field called `name`, keeping its position if it already exists and
+    /// appending it otherwise.
+    pub fn set(&mut self, name: &str, value: &str) {
+        let field = Field::new(name, value);
+
+        match self.entries.iter_mut().find_map(|entry| match entry {
+            Entry::Field(existing) if existing.name.eq_ignore_ascii_case(name) => Some(existing),
+            _ => None,
+        }) {
+            Some(existing) => existing.raw = field.raw,
+            None => self.entries.push(Entry::Field(field)),
+        }
+    }
+
+    /// Removes the field called `name` and returns it.
+    pub fn remove(&mut self, name: &str) -> Option<Field> {
+        let index = self.entries.iter().position(
+            |entry| matches!(entry, Entry::Field(field) if field.name.eq_ignore_ascii_case(name)),
+        )?;
+
+        match self.entries.remove(index) {
+            Entry::Field(field) => Some(field),
+            Entry::Comment(_) => None,
+        }
+    }
+
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        lines.extend(self.separator.iter().cloned());
+        self.write_entries(lines);
+    }
+
+    fn write_entries(&self, lines: &mut Vec<String>) {
+        for entry in &self.entries {
+            match entry {
+                Entry::Field(field) => lines.extend(
+                    format!("{}:{}", field.name, field.raw)
+                        .split('\n')
+                        .map(str::to_string),
+                ),
+                Entry::Comment(comment) => lines.push(comment.clone()),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Paragraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        self.write_entries(&mut lines);
+
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Paragraph {
+    type Err = Deb822Error;
+
+    /// Parses a single paragraph. Fails if the input contains more than one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut deb822 = s.parse::<Deb822>()?;
+
+        match deb822.paragraphs.len() {
+            0 => Ok(Paragraph::default()),
+            1 => {
+                let mut paragraph = deb822.paragraphs.remove(0);
+                paragraph.separator.clear();
+                Ok(paragraph)
+            }
+            _ => Err(Deb822Error::MultipleParagraphs),
+        }
+    }
+}
+
+/// The OpenPGP cleartext signature framework wrapped around a signed file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Signature {
+    /// The `BEGIN PGP SIGNED MESSAGE` line, armor headers and the following blank line.
+    header: Vec<String>,
+    /// The signature block, from `BEGIN PGP SIGNATURE` to `END PGP SIGNATURE`.
+    signature: Vec<String>,
+}
+
+/// A complete deb822 file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Deb822 {
+    pub paragraphs: Vec<Paragraph>,
+    /// Blank lines following the last paragraph.
+    trailing: Vec<String>,
+    signature: Option<Signature>,
+    final_newline: bool,
+}
+
+impl Default for Deb822 {
+    fn default() -> Self {
+        Self {
+            paragraphs: Vec::new(),
+            trailing: Vec::new(),
+            signature: None,
+            final_newline: true,
+        }
+    }
+}
+
+impl Deb822 {
+    /// Returns `true` if the paragraphs are wrapped in an OpenPGP cleartext signature. The
+    /// signature is not verified.
+    #[inline]
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    /// Adds a paragraph to the end of the file, separated by a blank line.
+    pub fn push(&mut self, mut paragraph: Paragraph) {
+        paragraph.separator = if self.paragraphs.is_empty() {
+            Vec::new()
+        } else {
+            vec![String::new()]
+        };
+        self.paragraphs.push(paragraph);
+    }
+}
+
+impl fmt::Display for Deb822 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut content = Vec::new();
+        for paragraph in &self.paragraphs {
+            paragraph.write_lines(&mut content);
+        }
+        content.extend(self.trailing.iter().cloned());
+
+        let lines = match self.signature {
+            Some(ref signature) => signature
+                .header
+                .iter()
+                .cloned()
+                .chain(content.into_iter().map(|line| {
+                    // Dash-escape lines that could be mistaken for armor lines.
+                    if line.starts_with('-') {
+                        format!("- {}", line)
+                    } else {
+                        line
+                    }
+                }))
+                .chain(signature.signature.iter().cloned())
+                .collect(),
+            None => content,
+        };
+
+        write!(f, "{}", lines.join("\n"))?;
+        if self.final_newline && !lines.is_empty() {
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Deb822 {
+    type Err = Deb822Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let final_newline = s.ends_with('\n');
+        let text = s.strip_suffix('\n').unwrap_or(s);
+        let lines = if text.is_empty() {
+            Vec::new()
+        } else {
+            text.split('\n').collect::<Vec<_>>()
+        };
+
+        let (signature, content, first_line) = if lines.first() == Some(&BEGIN_SIGNED_MESSAGE) {
+            let header_end = lines
+                .iter()
+                .position(|line| line.trim().is_empty())
+                .ok_or(Deb822Error::InvalidSignature)?;
+            let signature_start = lines
+                .iter()
+                .rposition(|line| *line == BEGIN_SIGNATURE)
+                .filter(|start| *start > header_end)
+                .ok_or(Deb822Error::InvalidSignature)?;
+            if lines.last() != Some(&END_SIGNATURE) {
+                return Err(Deb822Error::InvalidSignature);
+            }
+
+            let content = lines[header_end + 1..signature_start]
+                .iter()
+                .map(|line| line.strip_prefix("- ").unwrap_or(line))
+                .collect::<Vec<_>>();
+            let signature = Signature {
+                header: lines[..=header_end]
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+                signature: lines[signature_start..]
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect(),
+            };
+
+            (Some(signature), content, header_end + 2)
+        } else {
+            (None, lines, 1)
+        };
+
+        let mut paragraphs = Vec::new();
+        let mut separator = Vec::new();
+        let mut current: Option<Paragraph> = None;
+
+        for (index, line) in content.iter().copied().enumerate() {
+            let number = first_line + index;
+
+            if line.trim().is_empty() {
+                paragraphs.extend(current.take());
+                separator.push(line.to_string());
+                continue;
+            }
+
+            let paragraph = current.get_or_insert_with(|| Paragraph {
+                separator: std::mem::take(&mut separator),
+                entries: Vec::new(),
+            });
+
+            if line.starts_with('#') {
+                // Comments between continuation lines are part of the field.
+                let continues_field = content[index + 1..]
+                    .iter()
+                    .find(|line| !line.starts_with('#'))
+                    .is_some_and(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty());
+
+                match paragraph.entries.last_mut() {
+                    Some(Entry::Field(field)) if continues_field => {
+                        field.raw.push('\n');
+                        field.raw.push_str(line);
+                    }
+                    _ => paragraph.entries.push(Entry::Comment(line.to_string())),
+                }
+            } else if line.starts_with([' ', '\t']) {
+                match paragraph.entries.last_mut() {
+                    Some(Entry::Field(field)) => {
+                        field.raw.push('\n');
+                        field.raw.push_str(line);
+                    }
+                    _ => return Err(Deb822Error::ContinuationWithoutField(number)),
+                }
+            } else {
+                let (name, raw) = line
+                    .split_once(':')
+                    .ok_or(Deb822Error::InvalidField(number))?;
+                if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+                    return Err(Deb822Error::InvalidField(number));
+                }
+
+                paragraph.entries.push(Entry::Field(Field {
+                    name: name.to_string(),
+                    raw: raw.to_string(),
+                }));
+            }
+        }
+        paragraphs.extend(current);
+
+        Ok(Self {
+            paragraphs,
+            trailing: separator,
+            signature,
+            final_newline,
+        })
+    }
+}
+
+/// Iterator over the paragraphs of a deb822 file read line by line, see
+/// [`paragraphs`](crate::deb822::paragraphs).
+pub struct Paragraphs<R> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
+
+/// Reads the paragraphs of an unsigned deb822 file one at a time, without keeping the whole file
+/// in memory. Yields each paragraph with the number of its first line.
+pub fn paragraphs<R: BufRead>(reader: R) -> Paragraphs<R> {
+    Paragraphs {
+        lines: reader.lines(),
+        line: 0,
+    }
+}
+
+impl<R: BufRead> Iterator for Paragraphs<R> {
+    type Item = Result<(usize, Paragraph), IndexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = String::new();
+        let mut first_line = 0;
+
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            self.line += 1;
+
+            if line.trim().is_empty() {
+                if text.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if text.is_empty() {
+                first_line = self.line;
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(
+            text.parse::<Paragraph>()
+                .map(|paragraph| (first_line, paragraph))
+                .map_err(|error| {
+                    IndexError::Paragraph(first_line, error.offset_lines(first_line - 1))
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const CONTROL: &str = "\
+# Generated file, do not edit.
+Source: versian
+Maintainer: Jane Doe <jane@example.org>
+Build-Depends: debhelper-compat (= 13),
+               cargo,
+# rustc is pulled in by cargo
+               rustc (>= 1.70)
+
+Package: versian
+Architecture: any
+Description: Debian version parser
+ Parses and compares Debian versions.
+ .
+   Preformatted line.
+";
+
+    #[test]
+    fn parse() {
+        let deb822 = CONTROL.parse::<Deb822>().unwrap();
+        assert_eq!(deb822.paragraphs.len(), 2);
+
+        let source = &deb822.paragraphs[0];
+        assert_eq!(source.get("source").as_deref(), Some("versian"));
+        assert_eq!(
+            source.get("Build-Depends").as_deref(),
+            Some("debhelper-compat (= 13),\n              cargo,\n              rustc (>= 1.70)")
+        );
+        assert!(matches!(source.entries[0], Entry::Comment(_)));
+
+        let binary = &deb822.paragraphs[1];
+        assert_eq!(
+            binary.get("Description").as_deref(),
+            Some("Debian version parser\nParses and compares Debian versions.\n\n  Preformatted line.")
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in [
+            CONTROL,
+            CONTROL.trim_end(),
+            "\n\nA: b\n\n\n\nC: d\n \n\n",
+            "",
+        ] {
+            assert_eq!(input.parse::<Deb822>().unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn set_and_remove() {
+        let mut deb822 = CONTROL.parse::<Deb822>().unwrap();
+        let binary = &mut deb822.paragraphs[1];
+
+        binary.set("Architecture", "all");
+        binary.set("Multi-Arch", "foreign");
+        binary.set("Description", "Short\nLong\n\nMore");
+        assert_eq!(binary.remove("multi-arch").unwrap().value(), "foreign");
+
+        assert_eq!(
+            binary.to_string(),
+            "Package: versian\nArchitecture: all\nDescription: Short\n Long\n .\n More\n"
+        );
+    }
+
+    #[test]
+    fn signed() {
+        let signed = "\
+-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+Format: 3.0 (quilt)
+Source: versian
+- -dashed: is not a field
+
+-----BEGIN PGP SIGNATURE-----
+
+iQIzBAEBCAAdFiEE
+-----END PGP SIGNATURE-----
+";
+
+        assert_eq!(
+            signed.parse::<Deb822>().map(|deb822| deb822.is_signed()),
+            Err(Deb822Error::InvalidField(6))
+        );
+
+        let signed = signed.replace("- -dashed: is not a field", "Binary: versian");
+        let deb822 = signed.parse::<Deb822>().unwrap();
+        assert!(deb822.is_signed());
+        assert_eq!(deb822.paragraphs.len(), 1);
+        assert_eq!(
+            deb822.paragraphs[0].get("Binary").as_deref(),
+            Some("versian")
+        );
+        assert_eq!(deb822.to_string(), signed);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            " continuation".parse::<Deb822>(),
+            Err(Deb822Error::ContinuationWithoutField(1))
+        );
+        assert_eq!(
+            "A: b\nno colon".parse::<Deb822>(),
+            Err(Deb822Error::InvalidField(2))
+        );
+        assert_eq!(
+            "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\nA: b\n".parse::<Deb822>(),
+            Err(Deb822Error::InvalidSignature)
+        );
+        assert_eq!(
+            "A: b\n\nC: d\n".parse::<Paragraph>(),
+            Err(Deb822Error::MultipleParagraphs)
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let parsed = paragraphs(CONTROL.as_bytes())
+            .map(|paragraph| paragraph.unwrap())
+            .collect::<Vec<_>>();
+        let expected = CONTROL.parse::<Deb822>().unwrap().paragraphs;
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0], (1, expected[0].clone()));
+        assert_eq!(parsed[1].0, 9);
+        assert_eq!(parsed[1].1.get("Package"), expected[1].get("Package"));
+
+        let error = paragraphs("A: b\n\n\nC: d\nno colon\n".as_bytes())
+            .nth(1)
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            IndexError::Paragraph(4, Deb822Error::InvalidField(5))
+        ));
+    }
+}
@@ -1,44 +1,665 @@
-use std::{error, fmt, num::ParseIntError};
+use std::{error, fmt, io, num::ParseIntError, ops::Range};
 
-#[derive(Debug, PartialEq)]
+/// The component of a Debian version an error refers to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Component {
+    Epoch,
+    UpstreamVersion,
+    DebianRevision,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Component::Epoch => write!(f, "epoch"),
+            Component::UpstreamVersion => write!(f, "upstream version"),
+            Component::DebianRevision => write!(f, "Debian revision"),
+        }
+    }
+}
+
+/// Where in the input a [`DebianVersionError`](crate::error::DebianVersionError) occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// The complete input that failed to parse.
+    pub input: String,
+    /// Byte range of the offending part of `input`. Empty for missing components.
+    pub span: Range<usize>,
+    /// The offending character, if a single character is to blame.
+    pub character: Option<char>,
+    pub component: Component,
+}
+
+impl ErrorLocation {
+    pub(crate) fn new(
+        component: Component,
+        input: &str,
+        span: Range<usize>,
+        character: Option<char>,
+    ) -> Self {
+        Self {
+            input: input.to_string(),
+            span,
+            character,
+            component,
+        }
+    }
+
+    /// Points at the character starting at byte `index` of `input`.
+    pub(crate) fn at_char(component: Component, input: &str, index: usize) -> Self {
+        let character = input[index..].chars().next();
+        let end = index + character.map_or(0, char::len_utf8);
+
+        Self::new(component, input, index..end, character)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum DebianVersionError {
-    InvalidEpoch,
+    InvalidEpoch(ErrorLocation),
     Empty,
-    InvalidUpstream,
-    EmptyUpstream,
-    UpstreamStartWithDigit,
-    UpstreamInvalidCharacters,
-    EmptyRevision,
-    RevisionInvalidCharacters,
+    InvalidUpstream(ErrorLocation),
+    EmptyUpstream(ErrorLocation),
+    UpstreamStartWithDigit(ErrorLocation),
+    UpstreamInvalidCharacters(ErrorLocation),
+    EmptyRevision(ErrorLocation),
+    RevisionInvalidCharacters(ErrorLocation),
     InvalidFlags,
+    /// The version has a Debian revision, but the source format is native.
+    NativeWithRevision(ErrorLocation),
+    /// The version has no Debian revision, but the source format requires one.
+    MissingRevision(ErrorLocation),
+}
+
+impl DebianVersionError {
+    /// Returns the location of the error in the input, if the error refers to a specific part of
+    /// it.
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match *self {
+            DebianVersionError::InvalidEpoch(ref location)
+            | DebianVersionError::InvalidUpstream(ref location)
+            | DebianVersionError::EmptyUpstream(ref location)
+            | DebianVersionError::UpstreamStartWithDigit(ref location)
+            | DebianVersionError::UpstreamInvalidCharacters(ref location)
+            | DebianVersionError::EmptyRevision(ref location)
+            | DebianVersionError::RevisionInvalidCharacters(ref location)
+            | DebianVersionError::NativeWithRevision(ref location)
+            | DebianVersionError::MissingRevision(ref location) => Some(location),
+            DebianVersionError::Empty | DebianVersionError::InvalidFlags => None,
+        }
+    }
+
+    /// Returns the byte range of the offending part of the input.
+    #[inline]
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.location().map(|location| location.span.clone())
+    }
+
+    /// Returns the offending character, if a single character is to blame.
+    #[inline]
+    pub fn character(&self) -> Option<char> {
+        self.location().and_then(|location| location.character)
+    }
+
+    /// Returns the component of the version the error refers to.
+    #[inline]
+    pub fn component(&self) -> Option<Component> {
+        self.location().map(|location| location.component)
+    }
+
+    /// Moves the location of an error found in a component starting at byte `offset` of `input`,
+    /// so that it refers to `input` as a whole.
+    pub(crate) fn relocate(mut self, input: &str, offset: usize) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.input = input.to_string();
+            location.span = location.span.start + offset..location.span.end + offset;
+        }
+
+        self
+    }
+
+    fn location_mut(&mut self) -> Option<&mut ErrorLocation> {
+        match *self {
+            DebianVersionError::InvalidEpoch(ref mut location)
+            | DebianVersionError::InvalidUpstream(ref mut location)
+            | DebianVersionError::EmptyUpstream(ref mut location)
+            | DebianVersionError::UpstreamStartWithDigit(ref mut location)
+            | DebianVersionError::UpstreamInvalidCharacters(ref mut location)
+            | DebianVersionError::EmptyRevision(ref mut location)
+            | DebianVersionError::RevisionInvalidCharacters(ref mut location)
+            | DebianVersionError::NativeWithRevision(ref mut location)
+            | DebianVersionError::MissingRevision(ref mut location) => Some(location),
+            DebianVersionError::Empty | DebianVersionError::InvalidFlags => None,
+        }
+    }
 }
 
 impl From<ParseIntError> for DebianVersionError {
+    /// Converts a failure to parse an epoch. The error carries no input, so its location is
+    /// empty.
     fn from(_: ParseIntError) -> Self {
-        Self::InvalidEpoch
+        Self::InvalidEpoch(ErrorLocation::new(Component::Epoch, "", 0..0, None))
     }
 }
 
 impl fmt::Display for DebianVersionError {
+    /// Renders the error message, followed by the input with the offending part underlined if
+    /// the location of the error is known:
+    ///
+    /// ```text
+    /// Upstream version contains invalid characters (found '_' at byte 3).
+    ///   1.0_1-2
+    ///      ^
+    /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
-            DebianVersionError::InvalidEpoch => write!(f, "Epochs must be numeric."),
-            DebianVersionError::Empty => write!(f, "Version is empty."),
-            DebianVersionError::InvalidUpstream => write!(f, "Invalid upstream version."),
-            DebianVersionError::EmptyUpstream => write!(f, "Upstream version is empty."),
-            DebianVersionError::UpstreamStartWithDigit => {
-                write!(f, "Upstream version must start with a digit.")
+            DebianVersionError::InvalidEpoch(_) => write!(f, "Epochs must be numeric")?,
+            DebianVersionError::Empty => write!(f, "Version is empty")?,
+            DebianVersionError::InvalidUpstream(_) => write!(f, "Invalid upstream version")?,
+            DebianVersionError::EmptyUpstream(_) => write!(f, "Upstream version is empty")?,
+            DebianVersionError::UpstreamStartWithDigit(_) => {
+                write!(f, "Upstream version must start with a digit")?
+            }
+            DebianVersionError::UpstreamInvalidCharacters(_) => {
+                write!(f, "Upstream version contains invalid characters")?
             }
-            DebianVersionError::UpstreamInvalidCharacters => {
-                write!(f, "Upstream version contains invalid characters.")
+            DebianVersionError::EmptyRevision(_) => write!(f, "Debian revision is empty")?,
+            DebianVersionError::RevisionInvalidCharacters(_) => {
+                write!(f, "Debian revision contains invalid characters")?
             }
-            DebianVersionError::EmptyRevision => write!(f, "Debian revision is empty."),
-            DebianVersionError::RevisionInvalidCharacters => {
-                write!(f, "Debian revision contains invalid characters.")
+            DebianVersionError::InvalidFlags => write!(f, "Invalid flag combination")?,
+            DebianVersionError::NativeWithRevision(_) => {
+                write!(f, "Native source packages must not have a Debian revision")?
+            }
+            DebianVersionError::MissingRevision(_) => {
+                write!(f, "Non-native source packages must have a Debian revision")?
             }
-            DebianVersionError::InvalidFlags => write!(f, "Invalid flag combination."),
         }
+
+        let Some(location) = self
+            .location()
+            .filter(|location| !location.input.is_empty())
+        else {
+            return write!(f, ".");
+        };
+
+        match location.character {
+            Some(character) => write!(
+                f,
+                " (found {:?} at byte {}).",
+                character, location.span.start
+            )?,
+            None => write!(f, " (at byte {}).", location.span.start)?,
+        }
+
+        // Carets are aligned by character, not by byte, so that non-ASCII input lines up.
+        let column = location.input[..location.span.start].chars().count();
+        let width = location.input[location.span.clone()].chars().count().max(1);
+        write!(
+            f,
+            "\n  {}\n  {}{}",
+            location.input,
+            " ".repeat(column),
+            "^".repeat(width)
+        )
     }
 }
 
 impl error::Error for DebianVersionError {}
+
+/// Error parsing a [`SourceFormat`](crate::source_format::SourceFormat).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownSourceFormat(pub String);
+
+impl fmt::Display for UnknownSourceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown source format {}.", self.0)
+    }
+}
+
+impl error::Error for UnknownSourceFormat {}
+
+/// Error parsing a PEP 440 version, see [`pep440`](crate::pep440).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidPep440Version(pub String);
+
+impl fmt::Display for InvalidPep440Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid PEP 440 version {}.", self.0)
+    }
+}
+
+impl error::Error for InvalidPep440Version {}
+
+#[derive(Debug, PartialEq)]
+pub enum RelationError {
+    Empty,
+    InvalidPackageName,
+    InvalidArchitecture,
+    InvalidProfile,
+    InvalidOperator,
+    MissingVersion,
+    UnterminatedVersion,
+    UnterminatedArchitectures,
+    UnterminatedProfiles,
+    TrailingCharacters,
+    Version(DebianVersionError),
+}
+
+impl From<DebianVersionError> for RelationError {
+    fn from(error: DebianVersionError) -> Self {
+        Self::Version(error)
+    }
+}
+
+impl fmt::Display for RelationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RelationError::Empty => write!(f, "Relation is empty."),
+            RelationError::InvalidPackageName => write!(f, "Invalid package name."),
+            RelationError::InvalidArchitecture => write!(f, "Invalid architecture."),
+            RelationError::InvalidProfile => write!(f, "Invalid build profile."),
+            RelationError::InvalidOperator => write!(f, "Invalid version relation operator."),
+            RelationError::MissingVersion => write!(f, "Version relation is missing a version."),
+            RelationError::UnterminatedVersion => {
+                write!(f, "Version relation is missing a closing parenthesis.")
+            }
+            RelationError::UnterminatedArchitectures => {
+                write!(
+                    f,
+                    "Architecture restriction list is missing a closing bracket."
+                )
+            }
+            RelationError::UnterminatedProfiles => {
+                write!(
+                    f,
+                    "Build profile restriction is missing a closing angle bracket."
+                )
+            }
+            RelationError::TrailingCharacters => write!(f, "Unexpected characters after relation."),
+            RelationError::Version(ref error) => write!(f, "Invalid version: {}", error),
+        }
+    }
+}
+
+impl error::Error for RelationError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RelationError::Version(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Errors of the `debian/changelog` parser. Line numbers start at 1.
+#[derive(Debug, PartialEq)]
+pub enum ChangelogError {
+    Empty,
+    InvalidHeader(usize),
+    InvalidMetadata(usize),
+    InvalidTrailer(usize),
+    /// The date of an entry trailer isn't in RFC 2822 format.
+    InvalidDate(usize),
+    MissingTrailer(usize),
+    Version(usize, DebianVersionError),
+    /// The upstream version given for a new upstream release is invalid.
+    NewVersion(DebianVersionError),
+    /// The previous version is native and has no Debian revision to increment.
+    NativeVersion,
+    MissingPackage,
+    MissingMaintainer,
+    MissingVersion,
+    VersionNotNewer,
+}
+
+impl fmt::Display for ChangelogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ChangelogError::Empty => write!(f, "Changelog is empty."),
+            ChangelogError::InvalidHeader(line) => {
+                write!(f, "Line {}: Invalid changelog entry header.", line)
+            }
+            ChangelogError::InvalidMetadata(line) => {
+                write!(f, "Line {}: Invalid key=value pair in entry header.", line)
+            }
+            ChangelogError::InvalidTrailer(line) => {
+                write!(f, "Line {}: Invalid changelog entry trailer.", line)
+            }
+            ChangelogError::InvalidDate(line) => {
+                write!(f, "Line {}: Date isn't in RFC 2822 format.", line)
+            }
+            ChangelogError::MissingTrailer(line) => {
+                write!(f, "Line {}: Changelog entry has no trailer line.", line)
+            }
+            ChangelogError::Version(line, ref error) => {
+                write!(f, "Line {}: Invalid version: {}", line, error)
+            }
+            ChangelogError::NewVersion(ref error) => {
+                write!(f, "Invalid version of the new entry: {}", error)
+            }
+            ChangelogError::NativeVersion => write!(
+                f,
+                "Previous version is native and has no Debian revision to increment."
+            ),
+            ChangelogError::MissingPackage => write!(f, "New entry has no package name."),
+            ChangelogError::MissingMaintainer => write!(f, "New entry has no maintainer."),
+            ChangelogError::MissingVersion => {
+                write!(f, "Version of the new entry can't be determined.")
+            }
+            ChangelogError::VersionNotNewer => {
+                write!(
+                    f,
+                    "Version of the new entry isn't newer than the previous one."
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for ChangelogError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ChangelogError::Version(_, ref error) | ChangelogError::NewVersion(ref error) => {
+                Some(error)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Errors of the deb822 parser and the typed views built on it. Line numbers start at 1.
+#[derive(Debug, PartialEq)]
+pub enum Deb822Error {
+    InvalidField(usize),
+    ContinuationWithoutField(usize),
+    InvalidSignature,
+    MultipleParagraphs,
+    MissingField(&'static str),
+    /// A file list such as `Checksums-Sha256` contains a malformed line.
+    InvalidFileList(&'static str),
+    Version(DebianVersionError),
+    Relation(RelationError),
+    SourceFormat(UnknownSourceFormat),
+}
+
+impl Deb822Error {
+    /// Shifts line numbers by `offset` lines, for errors of a paragraph parsed out of a larger
+    /// file.
+    pub(crate) fn offset_lines(self, offset: usize) -> Self {
+        match self {
+            Deb822Error::InvalidField(line) => Deb822Error::InvalidField(line + offset),
+            Deb822Error::ContinuationWithoutField(line) => {
+                Deb822Error::ContinuationWithoutField(line + offset)
+            }
+            error => error,
+        }
+    }
+}
+
+impl From<DebianVersionError> for Deb822Error {
+    fn from(error: DebianVersionError) -> Self {
+        Self::Version(error)
+    }
+}
+
+impl From<RelationError> for Deb822Error {
+    fn from(error: RelationError) -> Self {
+        Self::Relation(error)
+    }
+}
+
+impl From<UnknownSourceFormat> for Deb822Error {
+    fn from(error: UnknownSourceFormat) -> Self {
+        Self::SourceFormat(error)
+    }
+}
+
+impl fmt::Display for Deb822Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Deb822Error::InvalidField(line) => write!(f, "Line {}: Invalid field.", line),
+            Deb822Error::ContinuationWithoutField(line) => {
+                write!(f, "Line {}: Continuation line outside of a field.", line)
+            }
+            Deb822Error::InvalidSignature => write!(f, "Invalid OpenPGP signature framework."),
+            Deb822Error::MultipleParagraphs => write!(f, "Expected a single paragraph."),
+            Deb822Error::MissingField(name) => write!(f, "Missing field {}.", name),
+            Deb822Error::InvalidFileList(name) => {
+                write!(f, "Field {} contains an invalid file entry.", name)
+            }
+            Deb822Error::Version(ref error) => write!(f, "Invalid version: {}", error),
+            Deb822Error::Relation(ref error) => write!(f, "Invalid relationship field: {}", error),
+            Deb822Error::SourceFormat(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for Deb822Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Deb822Error::Version(ref error) => Some(error),
+            Deb822Error::Relation(ref error) => Some(error),
+            Deb822Error::SourceFormat(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Errors parsing the file name of a package artifact.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileNameError {
+    /// The file name isn't of the form `package_version[_architecture].extension`.
+    Invalid,
+    UnknownExtension,
+    InvalidPackageName,
+    InvalidArchitecture,
+    /// The version contains an epoch, which never appears in file names.
+    ContainsEpoch,
+    Version(DebianVersionError),
+}
+
+impl From<DebianVersionError> for FileNameError {
+    fn from(error: DebianVersionError) -> Self {
+        Self::Version(error)
+    }
+}
+
+impl fmt::Display for FileNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FileNameError::Invalid => write!(
+                f,
+                "File name isn't of the form package_version[_architecture].extension."
+            ),
+            FileNameError::UnknownExtension => write!(f, "Unknown file extension."),
+            FileNameError::InvalidPackageName => write!(f, "Invalid package name."),
+            FileNameError::InvalidArchitecture => write!(f, "Invalid architecture."),
+            FileNameError::ContainsEpoch => {
+                write!(f, "Version in file name contains an epoch.")
+            }
+            FileNameError::Version(ref error) => write!(f, "Invalid version: {}", error),
+        }
+    }
+}
+
+impl error::Error for FileNameError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FileNameError::Version(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Inconsistencies between a source package and its files or changelog.
+#[derive(Debug, PartialEq)]
+pub enum SourceError {
+    /// No file matches the given expected orig tarball name prefix.
+    MissingOrigTarball(String),
+    /// The file name doesn't match the package name and version.
+    UnexpectedFile(String),
+    /// The changelog has no entries.
+    EmptyChangelog,
+    /// The package name differs from the one in the changelog, given second.
+    PackageMismatch(String, String),
+    /// The version differs from the one in the changelog, given second.
+    VersionMismatch(String, String),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SourceError::MissingOrigTarball(ref prefix) => {
+                write!(f, "No orig tarball named {}.tar.* found.", prefix)
+            }
+            SourceError::UnexpectedFile(ref name) => {
+                write!(
+                    f,
+                    "File {} doesn't match the package name and version.",
+                    name
+                )
+            }
+            SourceError::EmptyChangelog => write!(f, "Changelog is empty."),
+            SourceError::PackageMismatch(ref source, ref changelog) => write!(
+                f,
+                "Package {} doesn't match {} from the changelog.",
+                source, changelog
+            ),
+            SourceError::VersionMismatch(ref source, ref changelog) => write!(
+                f,
+                "Version {} doesn't match {} from the changelog.",
+                source, changelog
+            ),
+        }
+    }
+}
+
+impl error::Error for SourceError {}
+
+/// Errors reading an APT index file.
+#[derive(Debug)]
+pub enum IndexError {
+    Io(io::Error),
+    /// The paragraph starting at the given line is invalid.
+    Paragraph(usize, Deb822Error),
+    /// The file is compressed with the given format, but support for it isn't enabled.
+    UnsupportedCompression(&'static str),
+}
+
+impl From<io::Error> for IndexError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            IndexError::Io(ref error) => write!(f, "Failed to read index: {}", error),
+            IndexError::Paragraph(line, ref error) => {
+                write!(f, "Paragraph at line {}: {}", line, error)
+            }
+            IndexError::UnsupportedCompression(format) => {
+                write!(
+                    f,
+                    "Support for {} compressed indexes isn't enabled.",
+                    format
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for IndexError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            IndexError::Io(ref error) => Some(error),
+            IndexError::Paragraph(_, ref error) => Some(error),
+            IndexError::UnsupportedCompression(_) => None,
+        }
+    }
+}
+
+/// Errors converting between [`DebianVersion`](crate::DebianVersion) and `semver::Version`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SemverError {
+    /// The upstream version doesn't start with `major[.minor[.patch]]`.
+    InvalidRelease(String),
+    /// The part after `~` isn't a valid semver pre-release.
+    InvalidPrerelease(String),
+    /// The part after `+` isn't valid semver build metadata.
+    InvalidBuildMetadata(String),
+    Version(DebianVersionError),
+}
+
+impl From<DebianVersionError> for SemverError {
+    fn from(error: DebianVersionError) -> Self {
+        Self::Version(error)
+    }
+}
+
+impl fmt::Display for SemverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SemverError::InvalidRelease(ref upstream) => write!(
+                f,
+                "Upstream version {} doesn't start with major[.minor[.patch]].",
+                upstream
+            ),
+            SemverError::InvalidPrerelease(ref pre) => {
+                write!(f, "{} isn't a valid semver pre-release.", pre)
+            }
+            SemverError::InvalidBuildMetadata(ref build) => {
+                write!(f, "{} isn't valid semver build metadata.", build)
+            }
+            SemverError::Version(ref error) => write!(f, "Invalid version: {}", error),
+        }
+    }
+}
+
+impl error::Error for SemverError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SemverError::Version(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Errors parsing or applying `debian/watch` mangling rules.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MangleError {
+    /// The rule isn't of the form `s/pattern/replacement/flags` or `tr/from/to/flags`.
+    InvalidRule(String),
+    /// The pattern of the rule, given first, isn't a valid regular expression.
+    InvalidPattern(String, String),
+    /// The mangled upstream version is invalid.
+    Version(DebianVersionError),
+}
+
+impl From<DebianVersionError> for MangleError {
+    fn from(error: DebianVersionError) -> Self {
+        Self::Version(error)
+    }
+}
+
+impl fmt::Display for MangleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MangleError::InvalidRule(ref rule) => write!(f, "Invalid mangling rule {}.", rule),
+            MangleError::InvalidPattern(ref rule, ref message) => {
+                write!(f, "Invalid pattern in mangling rule {}: {}", rule, message)
+            }
+            MangleError::Version(ref error) => write!(f, "Invalid mangled version: {}", error),
+        }
+    }
+}
+
+impl error::Error for MangleError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            MangleError::Version(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
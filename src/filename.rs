@@ -0,0 +1,420 @@
+//! Canonical file names of source and binary package artifacts, and parsing of such file names
+//! back into package name, version and architecture.
+//!
+//! Epochs never appear in file names, and orig tarballs are named after the upstream version
+//! alone, e.g. version `1:1.2-3` of `foo` has the orig tarball `foo_1.2.orig.tar.xz` and the
+//! source description `foo_1.2-3.dsc`.
+
+use std::{fmt, str::FromStr};
+
+use crate::error::FileNameError;
+use crate::relation::{valid_architecture, valid_package_name};
+use crate::validations::ValidateUpstreamVersion;
+use crate::DebianVersion;
+
+/// Compression formats of source tarballs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Lzma,
+    Xz,
+}
+
+impl Compression {
+    /// Returns the file extension, e.g. `xz`.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            Compression::Gzip => "gz",
+            Compression::Bzip2 => "bz2",
+            Compression::Lzma => "lzma",
+            Compression::Xz => "xz",
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "gz" => Some(Compression::Gzip),
+            "bz2" => Some(Compression::Bzip2),
+            "lzma" => Some(Compression::Lzma),
+            "xz" => Some(Compression::Xz),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of a package artifact, which determines the suffix of its file name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// `foo_1.2.orig.tar.xz`, or `foo_1.2.orig-component.tar.xz` for additional tarballs.
+    OrigTarball {
+        component: Option<String>,
+        compression: Compression,
+    },
+    /// `foo_1.2-3.debian.tar.xz`, used by the `3.0 (quilt)` format.
+    DebianTarball(Compression),
+    /// `foo_1.2.tar.xz`, the single tarball of native packages.
+    NativeTarball(Compression),
+    /// `foo_1.2-3.diff.gz`, used by the `1.0` format.
+    DiffGz,
+    /// `foo_1.2-3.dsc`
+    Dsc,
+    /// `foo_1.2-3_amd64.deb`
+    Deb { architecture: String },
+    /// `foo_1.2-3_amd64.udeb`
+    Udeb { architecture: String },
+    /// `foo_1.2-3_amd64.changes`
+    Changes { architecture: String },
+    /// `foo_1.2-3_amd64.buildinfo`
+    Buildinfo { architecture: String },
+}
+
+/// The file name of a package artifact.
+///
+/// The version of orig tarballs is the upstream version only. Versions parsed from file names
+/// never have an epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactName {
+    pub package: String,
+    pub version: DebianVersion,
+    pub kind: ArtifactKind,
+}
+
+impl fmt::Display for ArtifactName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = match self.kind {
+            ArtifactKind::OrigTarball { .. } => self.version.upstream_version.clone(),
+            _ => self.version.without_epoch(),
+        };
+        write!(f, "{}_{}", self.package, version)?;
+
+        match self.kind {
+            ArtifactKind::OrigTarball {
+                ref component,
+                compression,
+            } => match component {
+                Some(component) => write!(f, ".orig-{}.tar.{}", component, compression.extension()),
+                None => write!(f, ".orig.tar.{}", compression.extension()),
+            },
+            ArtifactKind::DebianTarball(compression) => {
+                write!(f, ".debian.tar.{}", compression.extension())
+            }
+            ArtifactKind::NativeTarball(compression) => {
+                write!(f, ".tar.{}", compression.extension())
+            }
+            ArtifactKind::DiffGz => write!(f, ".diff.gz"),
+            ArtifactKind::Dsc => write!(f, ".dsc"),
+            ArtifactKind::Deb { ref architecture } => write!(f, "_{}.deb", architecture),
+            ArtifactKind::Udeb { ref architecture } => write!(f, "_{}.udeb", architecture),
+            ArtifactKind::Changes { ref architecture } => write!(f, "_{}.changes", architecture),
+            ArtifactKind::Buildinfo { ref architecture } => {
+                write!(f, "_{}.buildinfo", architecture)
+            }
+        }
+    }
+}
+
+impl FromStr for ArtifactName {
+    type Err = FileNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('_');
+        let (package, rest, architecture) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(package), Some(rest), architecture) if parts.next().is_none() => {
+                (package, rest, architecture)
+            }
+            _ => return Err(FileNameError::Invalid),
+        };
+        if !valid_package_name(package) {
+            return Err(FileNameError::InvalidPackageName);
+        }
+
+        let (version, kind) = match architecture {
+            Some(architecture) => {
+                let (architecture, extension) = architecture
+                    .rsplit_once('.')
+                    .ok_or(FileNameError::UnknownExtension)?;
+                if !valid_architecture(architecture) {
+                    return Err(FileNameError::InvalidArchitecture);
+                }
+
+                let architecture = architecture.to_string();
+                let kind = match extension {
+                    "deb" => ArtifactKind::Deb { architecture },
+                    "udeb" => ArtifactKind::Udeb { architecture },
+                    "changes" => ArtifactKind::Changes { architecture },
+                    "buildinfo" => ArtifactKind::Buildinfo { architecture },
+                    _ => return Err(FileNameError::UnknownExtension),
+                };
+
+                (parse_version(rest)?, kind)
+            }
+            None => parse_source_artifact(rest)?,
+        };
+
+        Ok(Self {
+            package: package.to_string(),
+            version,
+            kind,
+        })
+    }
+}
+
+/// Splits `version.suffix` of a source artifact into the version and the kind of artifact.
+fn parse_source_artifact(s: &str) -> Result<(DebianVersion, ArtifactKind), FileNameError> {
+    if let Some(version) = s.strip_suffix(".dsc") {
+        return Ok((parse_version(version)?, ArtifactKind::Dsc));
+    }
+    if let Some(version) = s.strip_suffix(".diff.gz") {
+        return Ok((parse_version(version)?, ArtifactKind::DiffGz));
+    }
+
+    let (rest, extension) = s.rsplit_once('.').ok_or(FileNameError::UnknownExtension)?;
+    let compression = Compression::from_extension(extension)
+        .filter(|_| rest.ends_with(".tar"))
+        .ok_or(FileNameError::UnknownExtension)?;
+    let rest = &rest[..rest.len() - ".tar".len()];
+
+    if let Some(version) = rest.strip_suffix(".debian") {
+        return Ok((
+            parse_version(version)?,
+            ArtifactKind::DebianTarball(compression),
+        ));
+    }
+    if let Some(version) = rest.strip_suffix(".orig") {
+        return Ok((
+            parse_upstream_version(version)?,
+            ArtifactKind::OrigTarball {
+                component: None,
+                compression,
+            },
+        ));
+    }
+    if let Some((version, component)) = rest.rsplit_once(".orig-") {
+        return Ok((
+            parse_upstream_version(version)?,
+            ArtifactKind::OrigTarball {
+                component: Some(component.to_string()),
+                compression,
+            },
+        ));
+    }
+
+    Ok((
+        parse_version(rest)?,
+        ArtifactKind::NativeTarball(compression),
+    ))
+}
+
+fn parse_version(s: &str) -> Result<DebianVersion, FileNameError> {
+    let version = s.parse::<DebianVersion>()?;
+    if version.epoch.is_some() {
+        return Err(FileNameError::ContainsEpoch);
+    }
+
+    Ok(version)
+}
+
+/// Parses the version of an orig tarball, which may contain hyphens since the package has a
+/// Debian revision.
+fn parse_upstream_version(s: &str) -> Result<DebianVersion, FileNameError> {
+    if s.contains(':') {
+        return Err(FileNameError::ContainsEpoch);
+    }
+    s.validate_with_revision()?;
+
+    Ok(DebianVersion {
+        epoch: None,
+        upstream_version: s.to_string(),
+        debian_revision: None,
+    })
+}
+
+impl DebianVersion {
+    /// Returns the version as it appears in file names, without the epoch.
+    pub fn without_epoch(&self) -> String {
+        match self.debian_revision {
+            Some(ref revision) => format!("{}-{}", self.upstream_version, revision),
+            None => self.upstream_version.clone(),
+        }
+    }
+}
+
+fn name(package: &str, version: &DebianVersion, kind: ArtifactKind) -> String {
+    ArtifactName {
+        package: package.to_string(),
+        version: version.clone(),
+        kind,
+    }
+    .to_string()
+}
+
+/// Returns the name of the orig tarball, e.g. `foo_1.2.orig.tar.xz` for `1:1.2-3`.
+pub fn orig_tarball(package: &str, version: &DebianVersion, compression: Compression) -> String {
+    name(
+        package,
+        version,
+        ArtifactKind::OrigTarball {
+            component: None,
+            compression,
+        },
+    )
+}
+
+/// Returns the name of an additional orig tarball, e.g. `foo_1.2.orig-docs.tar.xz`.
+pub fn orig_component_tarball(
+    package: &str,
+    version: &DebianVersion,
+    component: &str,
+    compression: Compression,
+) -> String {
+    name(
+        package,
+        version,
+        ArtifactKind::OrigTarball {
+            component: Some(component.to_string()),
+            compression,
+        },
+    )
+}
+
+/// Returns the name of the Debian tarball, e.g. `foo_1.2-3.debian.tar.xz`.
+pub fn debian_tarball(package: &str, version: &DebianVersion, compression: Compression) -> String {
+    name(package, version, ArtifactKind::DebianTarball(compression))
+}
+
+/// Returns the name of the tarball of a native package, e.g. `foo_1.2.tar.xz`.
+pub fn native_tarball(package: &str, version: &DebianVersion, compression: Compression) -> String {
+    name(package, version, ArtifactKind::NativeTarball(compression))
+}
+
+/// Returns the name of the source description, e.g. `foo_1.2-3.dsc`.
+pub fn dsc(package: &str, version: &DebianVersion) -> String {
+    name(package, version, ArtifactKind::Dsc)
+}
+
+/// Returns the name of a binary package, e.g. `foo_1.2-3_amd64.deb`.
+pub fn deb(package: &str, version: &DebianVersion, architecture: &str) -> String {
+    name(
+        package,
+        version,
+        ArtifactKind::Deb {
+            architecture: architecture.to_string(),
+        },
+    )
+}
+
+/// Returns the name of the upload description, e.g. `foo_1.2-3_source.changes`.
+pub fn changes(package: &str, version: &DebianVersion, architecture: &str) -> String {
+    name(
+        package,
+        version,
+        ArtifactKind::Changes {
+            architecture: architecture.to_string(),
+        },
+    )
+}
+
+/// Returns the name of the build information, e.g. `foo_1.2-3_amd64.buildinfo`.
+pub fn buildinfo(package: &str, version: &DebianVersion, architecture: &str) -> String {
+    name(
+        package,
+        version,
+        ArtifactKind::Buildinfo {
+            architecture: architecture.to_string(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn derive_names() {
+        let version = "1:1.2-3".parse::<DebianVersion>().unwrap();
+
+        assert_eq!(
+            orig_tarball("foo", &version, Compression::Xz),
+            "foo_1.2.orig.tar.xz"
+        );
+        assert_eq!(
+            orig_component_tarball("foo", &version, "docs", Compression::Gzip),
+            "foo_1.2.orig-docs.tar.gz"
+        );
+        assert_eq!(
+            debian_tarball("foo", &version, Compression::Xz),
+            "foo_1.2-3.debian.tar.xz"
+        );
+        assert_eq!(dsc("foo", &version), "foo_1.2-3.dsc");
+        assert_eq!(deb("foo", &version, "amd64"), "foo_1.2-3_amd64.deb");
+        assert_eq!(
+            changes("foo", &version, "source"),
+            "foo_1.2-3_source.changes"
+        );
+        assert_eq!(
+            buildinfo("foo", &version, "amd64"),
+            "foo_1.2-3_amd64.buildinfo"
+        );
+
+        let native = "2:1.2".parse::<DebianVersion>().unwrap();
+        assert_eq!(
+            native_tarball("foo", &native, Compression::Bzip2),
+            "foo_1.2.tar.bz2"
+        );
+    }
+
+    #[test]
+    fn parse_names() {
+        for name in [
+            "foo_1.2.orig.tar.xz",
+            "foo_1.2-rc1.orig-docs.tar.gz",
+            "foo_1.2-3.debian.tar.xz",
+            "foo_1.2.tar.lzma",
+            "foo_1.2-3.diff.gz",
+            "foo_1.2-3.dsc",
+            "foo_1.2-3_amd64.deb",
+            "foo-udeb_1.2-3_amd64.udeb",
+            "foo_1.2-3_source.changes",
+            "foo_1.2-3_amd64.buildinfo",
+        ] {
+            assert_eq!(name.parse::<ArtifactName>().unwrap().to_string(), name);
+        }
+
+        let deb = "libfoo1_2.0~rc1-1+b1_arm64.deb"
+            .parse::<ArtifactName>()
+            .unwrap();
+        assert_eq!(deb.package, "libfoo1");
+        assert_eq!(deb.version.to_string(), "2.0~rc1-1+b1");
+        assert_eq!(
+            deb.kind,
+            ArtifactKind::Deb {
+                architecture: "arm64".to_string()
+            }
+        );
+
+        let orig = "foo_1.2-rc1.orig-docs.tar.gz"
+            .parse::<ArtifactName>()
+            .unwrap();
+        assert_eq!(orig.version.upstream_version, "1.2-rc1");
+        assert_eq!(orig.version.debian_revision, None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |name: &str| name.parse::<ArtifactName>().unwrap_err();
+
+        assert_eq!(error("foo.deb"), FileNameError::Invalid);
+        assert_eq!(error("foo_1_2_amd64.deb"), FileNameError::Invalid);
+        assert_eq!(error("Foo_1.2.dsc"), FileNameError::InvalidPackageName);
+        assert_eq!(error("foo_1.2_amd64.rpm"), FileNameError::UnknownExtension);
+        assert_eq!(
+            error("foo_1.2.orig.tar.zst"),
+            FileNameError::UnknownExtension
+        );
+        assert_eq!(error("foo_1:1.2-3.dsc"), FileNameError::ContainsEpoch);
+        assert_eq!(error("foo_1:1.2.orig.tar.gz"), FileNameError::ContainsEpoch);
+        assert!(matches!(error("foo_a.dsc"), FileNameError::Version(_)));
+    }
+}
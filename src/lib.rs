@@ -1,22 +1,39 @@
-#![feature(let_chains)]
-#![feature(associated_type_defaults)]
-
-#[cfg(feature = "cmp")]
 use std::cmp::Ordering;
-#[cfg(feature = "cmp")]
-use std::ffi::CString;
 use std::{fmt, str::FromStr};
 
+pub mod bump;
+pub mod changelog;
+pub mod compare;
+pub mod control;
+pub mod dch;
+pub mod deb822;
 pub mod error;
+pub mod filename;
+pub mod lint;
+#[cfg(feature = "mangle")]
+pub mod mangle;
+pub mod optional;
+pub mod options;
+pub mod packages;
+pub mod pep440;
+pub mod range;
+pub mod relation;
+#[cfg(feature = "semver")]
+pub mod semver;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod source_format;
+pub mod sources;
 pub mod validations;
 
-use crate::error::DebianVersionError;
-use crate::validations::ValidateUpstreamVersion;
+use crate::error::{Component, DebianVersionError, ErrorLocation};
+use crate::options::ParseOptions;
+use crate::validations::{ValidateDebianRevision, ValidateUpstreamVersion};
 
 #[cfg(feature = "cmp")]
 use rust_apt::util::cmp_versions;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct DebianVersion {
     pub epoch: Option<usize>,
     pub upstream_version: String,
@@ -39,35 +56,231 @@ fn split_upstream_revision(s: &str) -> Result<(&str, Option<&str>)> {
         .map_or_else(|| (s, None), |(upt, rev)| (upt, Some(rev))))
 }
 
-pub type Version<T> = (Option<T>, T, Option<T>);
 pub type Result<T> = std::result::Result<T, DebianVersionError>;
 
-#[allow(dead_code)]
-#[allow(unused_variables)]
-fn parse_version<T: AsRef<str>>(s: T) -> Result<Version<T>> {
-    let s = s.as_ref();
-    let (epoch, rest) = s.split_once(':').unzip();
+/// A borrowed [`DebianVersion`](crate::DebianVersion), whose components are slices of the parsed
+/// string. Parsing into a `DebianVersionRef` never allocates.
+#[derive(Copy, Clone, Debug)]
+pub struct DebianVersionRef<'a> {
+    pub epoch: Option<&'a str>,
+    pub upstream_version: &'a str,
+    pub debian_revision: Option<&'a str>,
+}
+
+/// Parses `s` into a [`DebianVersionRef`](crate::DebianVersionRef) without copying any of its
+/// components. This performs the same validation as [`DebianVersion::from_str`].
+pub fn parse_version(s: &str) -> Result<DebianVersionRef<'_>> {
+    parse_components(s, &ParseOptions::strict(), &mut Vec::new())
+}
+
+/// Parses `s` like `dpkg` does, see [`ParseOptions::dpkg`](crate::options::ParseOptions::dpkg):
+/// problems `dpkg` only warns about are returned as warnings instead of failing.
+pub fn parse_version_lenient(s: &str) -> Result<(DebianVersionRef<'_>, Vec<DebianVersionError>)> {
+    ParseOptions::dpkg().parse(s)
+}
+
+/// Parses `s`, which has already been trimmed if requested, pushing errors tolerated by
+/// `options` to `warnings`.
+pub(crate) fn parse_components<'a>(
+    s: &'a str,
+    options: &ParseOptions,
+    warnings: &mut Vec<DebianVersionError>,
+) -> Result<DebianVersionRef<'a>> {
+    // A [`DebianVersion`] must never be empty.
+    bail_empty!(s);
+
+    // The Debian version string contains an epoch.
+    let (epoch, rest, offset) = match s.split_once(':') {
+        Some((epoch, rest)) if epoch.parse::<usize>().is_ok() => {
+            (Some(epoch), rest, epoch.len() + 1)
+        }
+        // Like APT, treat everything as upstream version if the prefix isn't a number.
+        Some(_) if options.lax_epoch => (None, s, 0),
+        Some((epoch, _)) => return Err(invalid_epoch(s, epoch)),
+        None => (None, s, 0),
+    };
+
+    // Validation errors refer to the component, relocate them to refer to `s`.
+    let mut check = |result: Result<bool>, offset: usize| match result {
+        Ok(_) => Ok(()),
+        Err(error) => {
+            let error = error.relocate(s, offset);
+            if options.tolerates(&error) {
+                warnings.push(error);
+                Ok(())
+            } else {
+                Err(error)
+            }
+        }
+    };
+    // `dpkg` tolerates invalid characters, but not whitespace.
+    let reject_whitespace = options.dpkg_warnings && !options.allow_whitespace;
+
+    match rest.rsplit_once('-') {
+        Some((upstream_version, debian_revision)) => {
+            let revision_offset = offset + upstream_version.len() + 1;
+            if reject_whitespace {
+                find_whitespace(s, upstream_version, offset, Component::UpstreamVersion)?;
+                find_whitespace(
+                    s,
+                    debian_revision,
+                    revision_offset,
+                    Component::DebianRevision,
+                )?;
+            }
+
+            check(upstream_version.validate_with_revision(), offset)?;
+            check(debian_revision.validate(), revision_offset)?;
+
+            Ok(DebianVersionRef {
+                epoch,
+                upstream_version,
+                debian_revision: Some(debian_revision),
+            })
+        }
+        None => {
+            if reject_whitespace {
+                find_whitespace(s, rest, offset, Component::UpstreamVersion)?;
+            }
 
-    if let Some(rest) = rest {
-        bail_empty!(rest);
+            check(rest.validate_without_revision(), offset)?;
 
-        let (upstream, revision) = rest
-            .split_once('-')
-            .unwrap_or_else(|| (rest, Default::default()));
+            Ok(DebianVersionRef {
+                epoch,
+                upstream_version: rest,
+                debian_revision: None,
+            })
+        }
     }
+}
 
-    todo!();
+/// Fails if `component`, which starts at byte `offset` of `input`, contains whitespace.
+fn find_whitespace(input: &str, component: &str, offset: usize, kind: Component) -> Result<()> {
+    match component.find(char::is_whitespace) {
+        Some(index) => {
+            let location = ErrorLocation::at_char(kind, input, offset + index);
+            Err(match kind {
+                Component::DebianRevision => {
+                    DebianVersionError::RevisionInvalidCharacters(location)
+                }
+                _ => DebianVersionError::UpstreamInvalidCharacters(location),
+            })
+        }
+        None => Ok(()),
+    }
+}
 
-    // todo!()
+/// Builds the error for the invalid epoch `epoch`, which is a prefix of `input`. Points at the
+/// first non-digit if there is one, otherwise at the whole epoch.
+fn invalid_epoch(input: &str, epoch: &str) -> DebianVersionError {
+    DebianVersionError::InvalidEpoch(match epoch.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => ErrorLocation::at_char(Component::Epoch, input, index),
+        None => ErrorLocation::new(Component::Epoch, input, 0..epoch.len(), None),
+    })
+}
+
+impl<'a> DebianVersionRef<'a> {
+    /// Parses `s` into a [`DebianVersionRef`](crate::DebianVersionRef), see
+    /// [`parse_version`](crate::parse_version).
+    #[inline]
+    pub fn parse(s: &'a str) -> Result<Self> {
+        parse_version(s)
+    }
+
+    /// Returns the numeric epoch of the [`DebianVersionRef`](crate::DebianVersionRef).
+    #[inline]
+    pub fn epoch(&self) -> Option<usize> {
+        // The epoch has been validated while parsing.
+        self.epoch.and_then(|epoch| epoch.parse().ok())
+    }
+
+    /// Returns the [`DebianVersionRef`](crate::DebianVersionRef) upstream version.
+    #[inline]
+    pub fn upstream_version(&self) -> &'a str {
+        self.upstream_version
+    }
+
+    /// Returns the [`DebianVersionRef`](crate::DebianVersionRef) Debian revision.
+    #[inline]
+    pub fn debian_revision(&self) -> Option<&'a str> {
+        self.debian_revision
+    }
+
+    /// Copies the borrowed components into an owned [`DebianVersion`](crate::DebianVersion).
+    #[inline]
+    pub fn to_owned(self) -> DebianVersion {
+        DebianVersion::from(self)
+    }
+}
+
+impl From<DebianVersionRef<'_>> for DebianVersion {
+    fn from(value: DebianVersionRef<'_>) -> Self {
+        Self {
+            epoch: value.epoch(),
+            upstream_version: value.upstream_version.to_string(),
+            debian_revision: value.debian_revision.map(str::to_string),
+        }
+    }
+}
+
+impl Ord for DebianVersionRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare::compare_version_refs(self, other)
+    }
+}
+
+impl PartialOrd for DebianVersionRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for DebianVersionRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DebianVersionRef<'_> {}
+
+impl fmt::Display for DebianVersionRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(epoch) = self.epoch {
+            write!(f, "{}:", epoch)?;
+        }
+
+        write!(f, "{}", self.upstream_version)?;
+
+        if let Some(revision) = self.debian_revision {
+            write!(f, "-{}", revision)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Ord for DebianVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare::compare_versions(self, other)
+    }
 }
 
-#[cfg(feature = "cmp")]
 impl PartialOrd for DebianVersion {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(cmp_versions(&self.to_string(), &other.to_string()))
+        Some(self.cmp(other))
     }
 }
 
+// Equality follows the version ordering, so that e.g. `1.0` and `1.0-0` are equal just like they
+// are for dpkg.
+impl PartialEq for DebianVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DebianVersion {}
+
 impl DebianVersion {
     /// Returns a formatted `DebianVersion` of the form: [epoch]:[upstream version]-[debian
     /// revision]. Epochs and debian revision substrings are optional.
@@ -76,6 +289,14 @@ impl DebianVersion {
         self.to_string()
     }
 
+    /// Compares two [`DebianVersion`](crate::DebianVersion)s using libapt-pkg instead of the
+    /// native implementation. Useful to cross-check the native ordering.
+    #[cfg(feature = "cmp")]
+    #[inline]
+    pub fn cmp_with_apt(&self, other: &Self) -> Ordering {
+        cmp_versions(&self.to_string(), &other.to_string())
+    }
+
     /// Returns the epoch of the [`DebianVersion`](), where an epoch is a (usually small) unsized
     /// integer.
     #[inline]
@@ -163,6 +384,11 @@ impl fmt::Display for DebianVersion {
 }
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(::serde::Serialize, ::serde::Deserialize),
+    serde(crate = "::serde", transparent)
+)]
 pub struct Epoch(pub usize);
 
 impl fmt::Display for Epoch {
@@ -175,13 +401,17 @@ impl FromStr for Epoch {
     type Err = DebianVersionError;
 
     fn from_str(s: &str) -> Result<Self> {
-        if s.is_empty() {
-            return Err(DebianVersionError::InvalidEpoch);
-        }
-
         match s.split_once(':') {
-            Some((epoch, _)) => Ok(Self(epoch.parse::<usize>()?)),
-            None => Err(DebianVersionError::InvalidEpoch),
+            Some((epoch, _)) => epoch
+                .parse::<usize>()
+                .map(Self)
+                .map_err(|_| invalid_epoch(s, epoch)),
+            None => Err(DebianVersionError::InvalidEpoch(ErrorLocation::new(
+                Component::Epoch,
+                s,
+                s.len()..s.len(),
+                None,
+            ))),
         }
     }
 }
@@ -193,73 +423,34 @@ pub enum VersionKinds {
     EpUpRe,
     /// Only upstream.
     U,
-    ///
+    /// Upstream and revision.
     UR,
 }
 
+impl DebianVersion {
+    /// Parses `s` leniently, see [`parse_version_lenient`](crate::parse_version_lenient). Returns
+    /// the version together with the problems that would make [`DebianVersion::from_str`] fail.
+    pub fn parse_lenient(s: &str) -> Result<(DebianVersion, Vec<DebianVersionError>)> {
+        DebianVersion::parse_with(s, &ParseOptions::dpkg())
+    }
+
+    /// Parses `s` according to `options`, returning the version together with the tolerated
+    /// problems.
+    pub fn parse_with(
+        s: &str,
+        options: &ParseOptions,
+    ) -> Result<(DebianVersion, Vec<DebianVersionError>)> {
+        options
+            .parse(s)
+            .map(|(version, warnings)| (version.to_owned(), warnings))
+    }
+}
+
 impl FromStr for DebianVersion {
     type Err = DebianVersionError;
 
     fn from_str(value: &str) -> Result<Self> {
-        // A [`DebianVersion`] must never be empty.
-        if value.is_empty() {
-            return Err(DebianVersionError::Empty);
-        }
-
-        let mut epoch = None;
-
-        // The Debian version string contains an epoch.
-        match value.split_once(':') {
-            Some((first, rest)) => {
-                epoch = match first.parse::<usize>() {
-                    Ok(inner) => Some(inner),
-                    Err(_) => return Err(DebianVersionError::InvalidEpoch),
-                };
-
-                if let Some((upstream_version, debian_revision)) = rest.rsplit_once('-') {
-                    if upstream_version.validate_with_revision()? {
-                        return Ok(Self {
-                            epoch,
-                            upstream_version: upstream_version.to_string(),
-                            debian_revision: Some(debian_revision.to_string()),
-                        });
-                    }
-                } else {
-                    if rest.validate_without_revision()? {
-                        return Ok(Self {
-                            epoch,
-                            upstream_version: rest.to_string(),
-                            debian_revision: None,
-                        });
-                    }
-                }
-            }
-            None => {
-                if let Some((upstream_version, debian_revision)) = value.rsplit_once('-') {
-                    if upstream_version.validate_with_revision()? {
-                        return Ok(Self {
-                            epoch,
-                            upstream_version: upstream_version.to_string(),
-                            debian_revision: Some(debian_revision.to_string()),
-                        });
-                    }
-                } else {
-                    if value.validate_without_revision()? {
-                        return Ok(Self {
-                            epoch,
-                            upstream_version: value.to_string(),
-                            debian_revision: None,
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(Self {
-            epoch,
-            upstream_version: value.to_string(),
-            debian_revision: None,
-        })
+        parse_version(value).map(DebianVersion::from)
     }
 }
 
@@ -269,6 +460,7 @@ mod tests {
     #[allow(unused_imports)]
     use more_asserts as ma;
     use pretty_assertions::assert_eq;
+    use std::ops::Range;
 
     #[test]
     fn empty() {
@@ -282,12 +474,133 @@ mod tests {
     #[test]
     fn only_dashes() {
         let parsed = "---".parse::<DebianVersion>();
-        let expected_error = DebianVersionError::UpstreamStartWithDigit;
+        let expected_error = DebianVersionError::UpstreamStartWithDigit(ErrorLocation {
+            input: "---".to_string(),
+            span: 0..1,
+            character: Some('-'),
+            component: Component::UpstreamVersion,
+        });
         let actual_error = parsed.unwrap_err();
 
         assert_eq!(actual_error, expected_error);
     }
 
+    #[test]
+    fn error_location() {
+        let actual_error = "1:2.0_1-1".parse::<DebianVersion>().unwrap_err();
+
+        assert!(matches!(
+            actual_error,
+            DebianVersionError::UpstreamInvalidCharacters(_)
+        ));
+        assert_eq!(actual_error.span(), Some(5..6));
+        assert_eq!(actual_error.character(), Some('_'));
+        assert_eq!(actual_error.component(), Some(Component::UpstreamVersion));
+        assert_eq!(
+            actual_error.to_string(),
+            "Upstream version contains invalid characters (found '_' at byte 5).\n  1:2.0_1-1\n       ^"
+        );
+
+        let actual_error = "1a:2.0".parse::<DebianVersion>().unwrap_err();
+        assert_eq!(actual_error.span(), Some(1..2));
+        assert_eq!(actual_error.component(), Some(Component::Epoch));
+        assert_eq!(
+            "".parse::<DebianVersion>().unwrap_err().to_string(),
+            "Version is empty."
+        );
+
+        let epoch_error = DebianVersionError::from("x".parse::<u32>().unwrap_err());
+        assert_eq!(epoch_error.component(), Some(Component::Epoch));
+        assert_eq!(epoch_error.to_string(), "Epochs must be numeric.");
+    }
+
+    #[test]
+    fn error_per_variant() {
+        use DebianVersionError::*;
+
+        let location = |input: &str, component, span: Range<usize>| {
+            let character = input[span.clone()].chars().next();
+            ErrorLocation::new(component, input, span, character)
+        };
+        let cases = [
+            (
+                "x:1.0",
+                InvalidEpoch(location("x:1.0", Component::Epoch, 0..1)),
+            ),
+            (
+                "1:-1",
+                EmptyUpstream(location("1:-1", Component::UpstreamVersion, 2..2)),
+            ),
+            (
+                "1:",
+                EmptyUpstream(location("1:", Component::UpstreamVersion, 2..2)),
+            ),
+            (
+                "v1.0-1",
+                UpstreamStartWithDigit(location("v1.0-1", Component::UpstreamVersion, 0..1)),
+            ),
+            (
+                "1.0_1",
+                UpstreamInvalidCharacters(location("1.0_1", Component::UpstreamVersion, 3..4)),
+            ),
+            (
+                "1.0-",
+                EmptyRevision(location("1.0-", Component::DebianRevision, 4..4)),
+            ),
+            (
+                "1.0-a_b",
+                RevisionInvalidCharacters(location("1.0-a_b", Component::DebianRevision, 5..6)),
+            ),
+            ("", Empty),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<DebianVersion>(), Err(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn revision_validation() {
+        let error = "1:2.0-1:3".parse::<DebianVersion>().unwrap_err();
+        assert_eq!(error.component(), Some(Component::DebianRevision));
+        assert_eq!(error.character(), Some(':'));
+        assert_eq!(error.span(), Some(7..8));
+
+        for valid in ["1.0-1", "1.0-1.2+b1~bpo12", "1.0-0ubuntu1", "1:1.0-a-b-1"] {
+            assert!(valid.parse::<DebianVersion>().is_ok(), "{}", valid);
+        }
+    }
+
+    #[test]
+    fn lenient() {
+        let (version, warnings) = DebianVersion::parse_lenient("v1.0_1-a_b").unwrap();
+        assert_eq!(version.upstream_version, "v1.0_1");
+        assert_eq!(version.debian_revision.as_deref(), Some("a_b"));
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(
+            warnings[0],
+            DebianVersionError::UpstreamStartWithDigit(_)
+        ));
+        assert!(matches!(
+            warnings[1],
+            DebianVersionError::RevisionInvalidCharacters(_)
+        ));
+        assert_eq!(warnings[1].span(), Some(8..9));
+
+        assert_eq!(
+            DebianVersion::parse_lenient("1.0-1").unwrap(),
+            ("1.0-1".parse().unwrap(), vec![])
+        );
+
+        // Errors `dpkg` rejects as well stay fatal.
+        for input in ["", "x:1.0", "1.0-", "1.0 1"] {
+            assert_eq!(
+                DebianVersion::parse_lenient(input).unwrap_err(),
+                input.parse::<DebianVersion>().unwrap_err()
+            );
+        }
+    }
+
     #[test]
     fn valid_version() {
         let version = "5.10.104-tegra-35.2.1-20230124153320";
@@ -303,7 +616,29 @@ mod tests {
         )
     }
 
-    #[cfg(feature = "cmp")]
+    #[test]
+    fn parse_borrowed() {
+        let version = "1:5.10.104-tegra-35.2.1-20230124153320";
+        let parsed = parse_version(version).unwrap();
+
+        assert_eq!(parsed.epoch, Some("1"));
+        assert_eq!(parsed.epoch(), Some(1));
+        assert_eq!(parsed.upstream_version, "5.10.104-tegra-35.2.1");
+        assert_eq!(parsed.debian_revision, Some("20230124153320"));
+        assert_eq!(parsed.to_string(), version);
+        assert_eq!(parsed.to_owned(), version.parse::<DebianVersion>().unwrap());
+    }
+
+    #[test]
+    fn parse_borrowed_shares_validation() {
+        for version in ["", "a1.0", "x:1.0", "1.0_1", "1:"] {
+            assert_eq!(
+                parse_version(version).map(DebianVersion::from),
+                version.parse::<DebianVersion>(),
+            );
+        }
+    }
+
     #[test]
     fn cmp_versions() {
         let parsed1 = DebianVersion::from_str("5.10.104-tegra-35.2.1-20230124153320");
@@ -312,4 +647,69 @@ mod tests {
         assert!(parsed2.is_ok());
         ma::assert_lt!(parsed1.unwrap(), parsed2.unwrap());
     }
+
+    #[test]
+    fn cmp_tilde_sorts_first() {
+        let versions = ["1.0~~", "1.0~~a", "1.0~", "1.0", "1.0a", "1.0+", "1.0.1"]
+            .iter()
+            .map(|v| v.parse::<DebianVersion>().unwrap())
+            .collect::<Vec<_>>();
+
+        for pair in versions.windows(2) {
+            ma::assert_lt!(pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn cmp_epoch_wins() {
+        let with_epoch = "1:0.1".parse::<DebianVersion>().unwrap();
+        let without_epoch = "9.9-9".parse::<DebianVersion>().unwrap();
+
+        ma::assert_gt!(with_epoch, without_epoch);
+        assert_eq!(
+            "0:1.0".parse::<DebianVersion>().unwrap(),
+            "1.0".parse::<DebianVersion>().unwrap()
+        );
+    }
+
+    #[test]
+    fn cmp_numeric_segments() {
+        ma::assert_lt!(
+            "1.9".parse::<DebianVersion>().unwrap(),
+            "1.10".parse::<DebianVersion>().unwrap()
+        );
+        assert_eq!(
+            "1.001".parse::<DebianVersion>().unwrap(),
+            "1.1".parse::<DebianVersion>().unwrap()
+        );
+        assert_eq!(
+            "1.0-0".parse::<DebianVersion>().unwrap(),
+            "1.0".parse::<DebianVersion>().unwrap()
+        );
+        ma::assert_lt!(
+            "1.0-1".parse::<DebianVersion>().unwrap(),
+            "1.0-1.1".parse::<DebianVersion>().unwrap()
+        );
+    }
+
+    #[cfg(feature = "cmp")]
+    #[test]
+    fn cmp_agrees_with_apt() {
+        let versions = [
+            "1.0~rc1-1",
+            "1.0-1",
+            "1:0.9-1",
+            "1.0+dfsg-1~bpo12+1",
+            "1.0a-1",
+        ]
+        .iter()
+        .map(|v| v.parse::<DebianVersion>().unwrap())
+        .collect::<Vec<_>>();
+
+        for a in &versions {
+            for b in &versions {
+                assert_eq!(a.cmp(b), a.cmp_with_apt(b));
+            }
+        }
+    }
 }
@@ -0,0 +1,357 @@
+//! Checks for versions that parse but are likely mistakes, similar to lintian tags.
+//!
+//! Every [`Lint`](crate::lint::Lint) has a stable ID, a [`Severity`](crate::lint::Severity) and a
+//! message, and points at the offending part of the version as rendered by `Display`.
+
+use std::{fmt, ops::Range};
+
+use crate::bump::is_number;
+use crate::DebianVersion;
+
+/// The upstream version contains a colon, but the version has no epoch.
+pub const UPSTREAM_COLON_WITHOUT_EPOCH: &str = "upstream-version-contains-colon-without-epoch";
+/// The epoch is `0`, which is the same as no epoch.
+pub const EPOCH_ZERO: &str = "epoch-zero";
+/// The epoch was increased although the version would be newer without it.
+pub const UNNECESSARY_EPOCH: &str = "unnecessary-epoch";
+/// The Debian revision is `0`, which usually indicates a missing revision.
+pub const DEBIAN_REVISION_ZERO: &str = "debian-revision-zero";
+/// The upstream version contains a hyphen, which is easily mistaken for the revision separator.
+pub const HYPHEN_IN_UPSTREAM_VERSION: &str = "hyphen-in-upstream-version";
+/// The version contains uppercase letters, which sort before all lowercase letters.
+pub const UPPERCASE_LETTERS: &str = "uppercase-letters-in-version";
+/// The version contains `~~`, which is rarely intended.
+pub const DOUBLE_TILDE: &str = "double-tilde-in-version";
+/// A numeric segment has leading zeros, which are ignored when comparing. The zero-padded month
+/// and day of calendar versions such as `2023.01.05` are accepted.
+pub const LEADING_ZEROS: &str = "leading-zeros-in-numeric-segment";
+/// A source version ends with a binNMU suffix, which is reserved for binary-only uploads.
+pub const BINNMU_SUFFIX_IN_SOURCE_VERSION: &str = "binnmu-suffix-in-source-version";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Severity::Info => write!(f, "I"),
+            Severity::Warning => write!(f, "W"),
+            Severity::Error => write!(f, "E"),
+        }
+    }
+}
+
+/// A finding of the lint checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// One of the IDs defined in this module, e.g. [`EPOCH_ZERO`](crate::lint::EPOCH_ZERO).
+    pub id: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Byte range of the offending part of the version as rendered by `Display`.
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Lint {
+    /// Renders the lint like lintian does, e.g. `W: epoch-zero: Epoch 0 is redundant.`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.id, self.message)
+    }
+}
+
+/// Checks a version of any kind.
+pub fn lint(version: &DebianVersion) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let upstream_start = version.epoch.map_or(0, |epoch| epoch.to_string().len() + 1);
+    let revision_start = upstream_start + version.upstream_version.len() + 1;
+    let mut push = |id, severity, message: String, span| {
+        lints.push(Lint {
+            id,
+            severity,
+            message,
+            span,
+        })
+    };
+
+    match version.epoch {
+        Some(0) => push(
+            EPOCH_ZERO,
+            Severity::Warning,
+            "Epoch 0 is redundant.".to_string(),
+            0..2,
+        ),
+        None => {
+            if let Some(index) = version.upstream_version.find(':') {
+                push(
+                    UPSTREAM_COLON_WITHOUT_EPOCH,
+                    Severity::Error,
+                    "Upstream version contains a colon, but the version has no epoch.".to_string(),
+                    index..index + 1,
+                );
+            }
+        }
+        Some(_) => {}
+    }
+
+    if let Some(index) = version
+        .debian_revision
+        .as_ref()
+        .and_then(|_| version.upstream_version.find('-'))
+    {
+        push(
+            HYPHEN_IN_UPSTREAM_VERSION,
+            Severity::Warning,
+            "Upstream version contains a hyphen, only the last one separates the Debian revision."
+                .to_string(),
+            upstream_start + index..upstream_start + index + 1,
+        );
+    }
+
+    if version.debian_revision.as_deref() == Some("0") {
+        push(
+            DEBIAN_REVISION_ZERO,
+            Severity::Warning,
+            "Debian revision 0 is unusual, revisions start at 1.".to_string(),
+            revision_start..revision_start + 1,
+        );
+    }
+
+    let components = std::iter::once((upstream_start, version.upstream_version.as_str())).chain(
+        version
+            .debian_revision
+            .as_deref()
+            .map(|revision| (revision_start, revision)),
+    );
+    for (start, component) in components {
+        if let Some(index) = component.find(|c: char| c.is_ascii_uppercase()) {
+            push(
+                UPPERCASE_LETTERS,
+                Severity::Info,
+                "Uppercase letters sort before all lowercase letters.".to_string(),
+                start + index..start + index + 1,
+            );
+        }
+
+        if let Some(index) = component.find("~~") {
+            push(
+                DOUBLE_TILDE,
+                Severity::Info,
+                "Version contains '~~'.".to_string(),
+                start + index..start + index + 2,
+            );
+        }
+
+        let segments = numeric_segments(component);
+        for (i, segment) in segments.iter().cloned().enumerate() {
+            // Calendar versions such as `2023.01.05` zero-pad the month and day on purpose.
+            let is_date = start == upstream_start && is_date_segment(component, &segments, i);
+            if segment.len() > 1 && component[segment.clone()].starts_with('0') && !is_date {
+                push(
+                    LEADING_ZEROS,
+                    Severity::Warning,
+                    format!(
+                        "Leading zeros of {} are ignored when comparing versions.",
+                        &component[segment.clone()]
+                    ),
+                    start + segment.start..start + segment.end,
+                );
+            }
+        }
+    }
+
+    lints
+}
+
+/// Checks the version of a source package, which additionally must not carry a binNMU suffix.
+pub fn lint_source(version: &DebianVersion) -> Vec<Lint> {
+    let mut lints = lint(version);
+    let rendered = version.to_string();
+
+    if let Some(index) = rendered.rfind("+b") {
+        if is_number(&rendered[index + 2..]) {
+            lints.push(Lint {
+                id: BINNMU_SUFFIX_IN_SOURCE_VERSION,
+                severity: Severity::Error,
+                message: "Source versions must not end with a binNMU suffix.".to_string(),
+                span: index..rendered.len(),
+            });
+        }
+    }
+
+    lints
+}
+
+/// Checks the version of a new source upload following `previous`, which additionally flags
+/// epochs that were increased without need.
+pub fn lint_upgrade(previous: &DebianVersion, version: &DebianVersion) -> Vec<Lint> {
+    let mut lints = lint_source(version);
+    let previous_epoch = previous.epoch.unwrap_or(0);
+
+    if version.epoch.unwrap_or(0) > previous_epoch {
+        let without_bump = DebianVersion {
+            epoch: previous.epoch,
+            ..version.clone()
+        };
+
+        if without_bump > *previous {
+            let epoch_len = version.epoch.map_or(0, |epoch| epoch.to_string().len());
+            lints.push(Lint {
+                id: UNNECESSARY_EPOCH,
+                severity: Severity::Warning,
+                message: format!(
+                    "Epoch isn't needed, {} is already newer than {}.",
+                    without_bump, previous
+                ),
+                span: 0..epoch_len,
+            });
+        }
+    }
+
+    lints
+}
+
+/// Returns the byte ranges of the runs of digits in `s`.
+fn numeric_segments(s: &str) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut start = None;
+
+    for (index, c) in s.char_indices() {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(segment_start)) => {
+                segments.push(segment_start..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(segment_start) = start {
+        segments.push(segment_start..s.len());
+    }
+
+    segments
+}
+
+/// Returns `true` if the `i`th of the numeric `segments` of `s` is the month or day of a date
+/// such as `2023.01.05` or `2023-01`.
+fn is_date_segment(s: &str, segments: &[Range<usize>], i: usize) -> bool {
+    let number = |segment: &Range<usize>| s[segment.clone()].parse::<u32>().unwrap_or(u32::MAX);
+    let adjacent = |previous: &Range<usize>, segment: &Range<usize>| {
+        segment.start == previous.end + 1 && matches!(s.as_bytes()[previous.end], b'.' | b'-')
+    };
+    if i == 0 || !adjacent(&segments[i - 1], &segments[i]) || segments[i].len() > 2 {
+        return false;
+    }
+
+    let previous = &segments[i - 1];
+    let is_month = |segment| (1..=12).contains(&number(segment));
+    let is_year =
+        |segment: &Range<usize>| segment.len() == 4 && (1900..3000).contains(&number(segment));
+
+    if is_month(&segments[i]) && is_year(previous) {
+        return true;
+    }
+
+    // A day follows a month, which follows a year.
+    (1..=31).contains(&number(&segments[i]))
+        && i >= 2
+        && is_year(&segments[i - 2])
+        && is_date_segment(s, segments, i - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn ids(lints: Vec<Lint>) -> Vec<&'static str> {
+        lints.into_iter().map(|lint| lint.id).collect()
+    }
+
+    fn version(s: &str) -> DebianVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn clean_versions() {
+        for clean in [
+            "1.2-3",
+            "1:2.0~rc1-1",
+            "2.10+dfsg-1.1",
+            "1.0+b1-1",
+            "20230101",
+            "2023.01.05-1",
+            "2023.1.05-1",
+            "1.2023.12",
+        ] {
+            assert_eq!(lint(&version(clean)), vec![], "{}", clean);
+        }
+    }
+
+    #[test]
+    fn lints() {
+        assert_eq!(ids(lint(&version("0:1.2-3"))), [EPOCH_ZERO]);
+        assert_eq!(ids(lint(&version("1.2-0"))), [DEBIAN_REVISION_ZERO]);
+        assert_eq!(
+            ids(lint(&version("1.2-rc1-1"))),
+            [HYPHEN_IN_UPSTREAM_VERSION]
+        );
+        assert_eq!(ids(lint(&version("1.2RC1-1"))), [UPPERCASE_LETTERS]);
+        assert_eq!(ids(lint(&version("1.2~~a-1"))), [DOUBLE_TILDE]);
+        assert_eq!(
+            ids(lint(&version("1.02-1.01"))),
+            [LEADING_ZEROS, LEADING_ZEROS]
+        );
+        // Only the month and day of a calendar version may be zero-padded.
+        assert_eq!(ids(lint(&version("2023.01.05.01"))), [LEADING_ZEROS]);
+        assert_eq!(ids(lint(&version("2023.13.05"))), [LEADING_ZEROS]);
+        assert_eq!(ids(lint(&version("1.2023.01-01"))), [LEADING_ZEROS]);
+
+        let colon = DebianVersion {
+            epoch: None,
+            upstream_version: "1:2".to_string(),
+            debian_revision: None,
+        };
+        assert_eq!(ids(lint(&colon)), [UPSTREAM_COLON_WITHOUT_EPOCH]);
+    }
+
+    #[test]
+    fn spans() {
+        let lints = lint(&version("1:1.02-0"));
+
+        assert_eq!(lints[0].id, DEBIAN_REVISION_ZERO);
+        assert_eq!(lints[0].span, 7..8);
+        assert_eq!(lints[1].id, LEADING_ZEROS);
+        assert_eq!(lints[1].span, 4..6);
+        assert_eq!(
+            lints[1].to_string(),
+            "W: leading-zeros-in-numeric-segment: Leading zeros of 02 are ignored when comparing versions."
+        );
+    }
+
+    #[test]
+    fn source_and_upgrade_lints() {
+        assert_eq!(
+            ids(lint_source(&version("1.2-3+b1"))),
+            [BINNMU_SUFFIX_IN_SOURCE_VERSION]
+        );
+        assert_eq!(
+            ids(lint_source(&version("1.2+b1"))),
+            [BINNMU_SUFFIX_IN_SOURCE_VERSION]
+        );
+        assert_eq!(ids(lint_source(&version("1.2-3+bpo1"))), Vec::<&str>::new());
+
+        assert_eq!(
+            ids(lint_upgrade(&version("1.2-3"), &version("1:1.3-1"))),
+            [UNNECESSARY_EPOCH]
+        );
+        assert_eq!(
+            ids(lint_upgrade(&version("1.2-3"), &version("1:1.1-1"))),
+            Vec::<&str>::new()
+        );
+    }
+}
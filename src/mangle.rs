@@ -0,0 +1,383 @@
+//! Mangling rules as used by the `uversionmangle` and `dversionmangle` options of
+//! `debian/watch` files, enabled with the `mangle` feature, see uscan(1).
+//!
+//! A rule is either a substitution `s/pattern/replacement/flags` or a transliteration
+//! `tr/from/to/flags` (or `y/from/to/flags`), and rules are separated by `;`. Patterns are
+//! regular expressions as supported by the `regex` crate, which covers the Perl syntax commonly
+//! used in watch files except for look-around and backreferences. Replacements may refer to
+//! groups as `$1`, `${1}` or `\1`, and to the whole match as `$&`.
+
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::error::MangleError;
+use crate::validations::ValidateUpstreamVersion;
+use crate::DebianVersion;
+
+/// The pattern uscan substitutes for `@DEB_EXT@`, matching repacking suffixes such as `+dfsg`,
+/// `+ds1` or `~deb.2`.
+pub const DEB_EXT: &str = r"[\+~](debian|dfsg|ds|deb)(\.)?(\d+)?$";
+
+/// A single substitution or transliteration.
+#[derive(Clone, Debug)]
+pub enum MangleRule {
+    /// `s/pattern/replacement/flags`, with the replacement in the syntax of the `regex` crate.
+    Substitute {
+        regex: Regex,
+        replacement: String,
+        /// The `g` flag, replacing all matches instead of the first one.
+        global: bool,
+    },
+    /// `tr/from/to/flags` with expanded character ranges.
+    Transliterate {
+        from: Vec<char>,
+        to: Vec<char>,
+        /// The `d` flag, deleting characters of `from` without counterpart in `to`.
+        delete: bool,
+    },
+}
+
+impl MangleRule {
+    pub fn apply(&self, s: &str) -> String {
+        match *self {
+            MangleRule::Substitute {
+                ref regex,
+                ref replacement,
+                global: true,
+            } => regex.replace_all(s, replacement.as_str()).into_owned(),
+            MangleRule::Substitute {
+                ref regex,
+                ref replacement,
+                global: false,
+            } => regex.replace(s, replacement.as_str()).into_owned(),
+            MangleRule::Transliterate {
+                ref from,
+                ref to,
+                delete,
+            } => s
+                .chars()
+                .filter_map(|c| match from.iter().position(|&from| from == c) {
+                    None => Some(c),
+                    Some(index) if index < to.len() => Some(to[index]),
+                    // Like Perl, repeat the last character of a shorter replacement list.
+                    Some(_) if !delete => Some(to.last().copied().unwrap_or(c)),
+                    Some(_) => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for MangleRule {
+    type Err = MangleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim();
+        let invalid = || MangleError::InvalidRule(rule.to_string());
+
+        let (transliterate, rest) = match rule.strip_prefix("tr").or(rule.strip_prefix('y')) {
+            Some(rest) => (true, rest),
+            None => (false, rule.strip_prefix('s').ok_or_else(invalid)?),
+        };
+        let open = rest
+            .chars()
+            .next()
+            .filter(|c| !(c.is_alphanumeric() || c.is_whitespace() || *c == '\\'))
+            .ok_or_else(invalid)?;
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            '{' => '}',
+            '<' => '>',
+            _ => open,
+        };
+
+        let (first, rest) =
+            split_part(&rest[open.len_utf8()..], open, close).ok_or_else(invalid)?;
+        let rest = if open == close {
+            rest
+        } else {
+            rest.trim_start().strip_prefix(open).ok_or_else(invalid)?
+        };
+        let (second, flags) = split_part(rest, open, close).ok_or_else(invalid)?;
+
+        if transliterate {
+            let delete = match flags {
+                "" => false,
+                "d" => true,
+                _ => return Err(invalid()),
+            };
+            return Ok(MangleRule::Transliterate {
+                from: expand_ranges(first).ok_or_else(invalid)?,
+                to: expand_ranges(second).ok_or_else(invalid)?,
+                delete,
+            });
+        }
+
+        let mut builder = RegexBuilder::new(&first.replace("@DEB_EXT@", DEB_EXT));
+        let mut global = false;
+        for flag in flags.chars() {
+            match flag {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                'x' => {
+                    builder.ignore_whitespace(true);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(MangleRule::Substitute {
+            regex: builder.build().map_err(|error| {
+                MangleError::InvalidPattern(rule.to_string(), error.to_string())
+            })?,
+            replacement: replacement(second, close),
+            global,
+        })
+    }
+}
+
+/// Rules as given in a `uversionmangle` or `dversionmangle` option, applied in order.
+#[derive(Clone, Debug, Default)]
+pub struct ManglingRules(pub Vec<MangleRule>);
+
+impl ManglingRules {
+    /// The rules of `dversionmangle=auto`, which strip a repacking suffix, see
+    /// [`DEB_EXT`](crate::mangle::DEB_EXT).
+    pub fn auto() -> Self {
+        ManglingRules(vec!["s/@DEB_EXT@//"
+            .parse()
+            .expect("the built-in rule is valid")])
+    }
+
+    pub fn apply(&self, s: &str) -> String {
+        self.0
+            .iter()
+            .fold(s.to_string(), |mangled, rule| rule.apply(&mangled))
+    }
+}
+
+impl FromStr for ManglingRules {
+    type Err = MangleError;
+
+    /// Parses rules separated by `;`. `auto` yields [`ManglingRules::auto`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "auto" {
+            return Ok(ManglingRules::auto());
+        }
+
+        s.split(';')
+            .filter(|rule| !rule.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(ManglingRules)
+    }
+}
+
+impl DebianVersion {
+    /// Applies `rules` to the upstream version, e.g. `dversionmangle` rules to obtain the version
+    /// of the upstream release a package was built from. Unlike
+    /// [`map_upstream_version_with`](crate::DebianVersion::map_upstream_version_with), which
+    /// modifies the upstream version in place, rules may change its length. Fails if the mangled
+    /// upstream version is invalid, in which case the version is left unchanged.
+    pub fn mangle_upstream_version(&mut self, rules: &ManglingRules) -> Result<(), MangleError> {
+        let mangled = rules.apply(&self.upstream_version);
+        if self.debian_revision.is_some() {
+            mangled.validate_with_revision()?;
+        } else {
+            mangled.validate_without_revision()?;
+        }
+
+        self.upstream_version = mangled;
+        Ok(())
+    }
+}
+
+/// Splits `s` at the first unescaped `close` outside of nested `open`/`close` pairs.
+fn split_part(s: &str, open: char, close: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    let mut chars = s.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == close && depth == 0 {
+            return Some((&s[..index], &s[index + c.len_utf8()..]));
+        } else if c == close {
+            depth -= 1;
+        } else if c == open {
+            depth += 1;
+        }
+    }
+
+    None
+}
+
+/// Converts a Perl replacement into the syntax of the `regex` crate.
+fn replacement(s: &str, delimiter: char) -> String {
+    let mut converted = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().copied()) {
+            ('\\', Some(digit)) | ('$', Some(digit)) if digit.is_ascii_digit() => {
+                let mut group = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    group.push(digit);
+                }
+                converted.push_str(&format!("${{{}}}", group));
+            }
+            ('$', Some('&')) => {
+                chars.next();
+                converted.push_str("${0}");
+            }
+            ('$', Some('{')) => {
+                converted.push('$');
+            }
+            ('\\', Some(escaped)) => {
+                chars.next();
+                if escaped == '$' {
+                    converted.push_str("$$");
+                } else if escaped == delimiter || !escaped.is_alphanumeric() {
+                    converted.push(escaped);
+                } else {
+                    converted.push('\\');
+                    converted.push(escaped);
+                }
+            }
+            ('$', _) => converted.push_str("$$"),
+            _ => converted.push(c),
+        }
+    }
+
+    converted
+}
+
+/// Expands the ranges of a transliteration list, e.g. `a-c` into `abc`. Returns `None` for
+/// reversed ranges such as `z-a`, which Perl rejects.
+fn expand_ranges(s: &str) -> Option<Vec<char>> {
+    let mut chars = Vec::new();
+    let mut input = s.chars().peekable();
+
+    while let Some(c) = input.next() {
+        let c = if c == '\\' {
+            input.next().unwrap_or(c)
+        } else {
+            c
+        };
+        match input.peek() {
+            Some('-') => {
+                input.next();
+                match input.next() {
+                    Some(end) if end < c => return None,
+                    Some(end) => chars.extend(c..=end),
+                    None => chars.extend([c, '-']),
+                }
+            }
+            _ => chars.push(c),
+        }
+    }
+
+    Some(chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DebianVersionError;
+    use pretty_assertions::assert_eq;
+
+    fn mangle(rules: &str, s: &str) -> String {
+        rules.parse::<ManglingRules>().unwrap().apply(s)
+    }
+
+    #[test]
+    fn substitutions() {
+        assert_eq!(mangle("s/-rc/~rc/", "1.0-rc1"), "1.0~rc1");
+        assert_eq!(mangle("s/-/./g", "1-2-3"), "1.2.3");
+        assert_eq!(mangle("s/-/./", "1-2-3"), "1.2-3");
+        assert_eq!(mangle("s/RC/~rc/i", "1.0rc1"), "1.0~rc1");
+        assert_eq!(mangle(r"s/(\d+)_(\d+)/\1.$2/", "v1_2"), "v1.2");
+        assert_eq!(mangle(r"s/^v(.*)$/${1}a/", "v1.2"), "1.2a");
+        assert_eq!(mangle(r"s/$/+dfsg/", "1.2"), "1.2+dfsg");
+        assert_eq!(mangle(r"s/\./$&$&/", "1.2"), "1..2");
+        assert_eq!(mangle(r"s|\/|\||", "1/2"), "1|2");
+        assert_eq!(mangle(r"s{\+ds\d*}{}", "1.2+ds1"), "1.2");
+        assert_eq!(mangle(r"s/\$/\$US/", "1$"), "1$US");
+    }
+
+    #[test]
+    fn transliterations() {
+        assert_eq!(mangle("tr/A-Z/a-z/", "1.0RC1"), "1.0rc1");
+        assert_eq!(mangle("y/_-/../", "1_2-3"), "1.2.3");
+        assert_eq!(mangle("tr/abc/x/", "cab"), "xxx");
+        assert_eq!(mangle("tr/a-c//d", "1a2b3c"), "123");
+    }
+
+    #[test]
+    fn multiple_rules() {
+        assert_eq!(
+            mangle("s/\\+dfsg\\d*$//; s/~repack$//;", "2.4+dfsg2"),
+            "2.4"
+        );
+        assert_eq!(mangle("s/-/./g;s/^/0./", "1-2"), "0.1.2");
+    }
+
+    #[test]
+    fn auto() {
+        for (version, upstream) in [
+            ("1.2+dfsg", "1.2"),
+            ("1.2+ds1", "1.2"),
+            ("1.2~deb.2", "1.2"),
+            ("1.2+really", "1.2+really"),
+        ] {
+            assert_eq!(mangle("auto", version), upstream, "{}", version);
+        }
+    }
+
+    #[test]
+    fn mangle_upstream_version() {
+        let mut version = "1:2.4+dfsg~repack-3".parse::<DebianVersion>().unwrap();
+        let rules = r"s/~repack$//;s/@DEB_EXT@//".parse().unwrap();
+
+        version.mangle_upstream_version(&rules).unwrap();
+        assert_eq!(version.to_string(), "1:2.4-3");
+
+        let error = version
+            .mangle_upstream_version(&"s/^/v/".parse().unwrap())
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            MangleError::Version(DebianVersionError::UpstreamStartWithDigit(_))
+        ));
+        assert_eq!(version.upstream_version, "2.4");
+    }
+
+    #[test]
+    fn invalid_rules() {
+        for rule in [
+            "x/a/b/",
+            "s/a/b",
+            "s/a/b/q",
+            "tr/a/b/g",
+            "sa",
+            "s{a}b",
+            "tr/z-a/x/",
+            "y/a/9-0/",
+        ] {
+            assert_eq!(
+                rule.parse::<MangleRule>().unwrap_err(),
+                MangleError::InvalidRule(rule.to_string()),
+                "{}",
+                rule
+            );
+        }
+        assert!(matches!(
+            "s/(/x/".parse::<MangleRule>(),
+            Err(MangleError::InvalidPattern(_, _))
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+//! Versions that may be absent, e.g. the installed version of a package that isn't installed.
+//!
+//! `dpkg --compare-versions` accepts an empty version, which is earlier than all other versions
+//! for the usual operators and later than all of them for `lt-nl`, `le-nl`, `ge-nl` and `gt-nl`.
+//! [`OptionalVersion`](crate::optional::OptionalVersion) supports both with
+//! [`EmptyVersion`](crate::optional::EmptyVersion).
+
+use std::cmp::Ordering;
+use std::{fmt, str::FromStr};
+
+use crate::error::DebianVersionError;
+use crate::DebianVersion;
+
+/// Where an absent version sorts relative to all present versions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EmptyVersion {
+    /// Earlier than all versions, like for `dpkg --compare-versions` with `lt`, `eq`, `gt` etc.
+    #[default]
+    Earliest,
+    /// Later than all versions, like for the `-nl` operators of `dpkg --compare-versions`.
+    Latest,
+}
+
+/// Compares two versions that may be absent. Two absent versions are equal.
+pub fn compare_optional_versions(
+    a: Option<&DebianVersion>,
+    b: Option<&DebianVersion>,
+    empty: EmptyVersion,
+) -> Ordering {
+    let empty_first = match empty {
+        EmptyVersion::Earliest => Ordering::Less,
+        EmptyVersion::Latest => Ordering::Greater,
+    };
+
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => empty_first,
+        (Some(_), None) => empty_first.reverse(),
+    }
+}
+
+/// A version that may be absent. Absent versions are earlier than all others, as in dpkg; use
+/// [`cmp_with`](crate::optional::OptionalVersion::cmp_with) to treat them as the latest instead.
+///
+/// It is rendered as and parsed from the empty string when absent. Parsing also accepts
+/// `<unknown>`, which dpkg uses for missing versions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OptionalVersion(pub Option<DebianVersion>);
+
+impl OptionalVersion {
+    /// The absent version.
+    pub const NONE: Self = OptionalVersion(None);
+
+    pub fn is_none(&self) -> bool {
+        self.0.is_none()
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn as_ref(&self) -> Option<&DebianVersion> {
+        self.0.as_ref()
+    }
+
+    /// Compares the versions, sorting an absent version as specified by `empty`.
+    pub fn cmp_with(&self, other: &Self, empty: EmptyVersion) -> Ordering {
+        compare_optional_versions(self.as_ref(), other.as_ref(), empty)
+    }
+}
+
+impl Ord for OptionalVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with(other, EmptyVersion::Earliest)
+    }
+}
+
+impl PartialOrd for OptionalVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<DebianVersion> for OptionalVersion {
+    fn eq(&self, other: &DebianVersion) -> bool {
+        self.as_ref() == Some(other)
+    }
+}
+
+impl fmt::Display for OptionalVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(version) => write!(f, "{}", version),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for OptionalVersion {
+    type Err = DebianVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "<unknown>" => Ok(OptionalVersion::NONE),
+            _ => s.parse().map(|version| OptionalVersion(Some(version))),
+        }
+    }
+}
+
+impl From<DebianVersion> for OptionalVersion {
+    fn from(version: DebianVersion) -> Self {
+        OptionalVersion(Some(version))
+    }
+}
+
+impl From<Option<DebianVersion>> for OptionalVersion {
+    fn from(version: Option<DebianVersion>) -> Self {
+        OptionalVersion(version)
+    }
+}
+
+impl From<OptionalVersion> for Option<DebianVersion> {
+    fn from(version: OptionalVersion) -> Self {
+        version.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn optional(s: &str) -> OptionalVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(optional(""), OptionalVersion::NONE);
+        assert_eq!(optional("<unknown>"), OptionalVersion::NONE);
+        assert_eq!(optional("1:2.0-3").to_string(), "1:2.0-3");
+        assert_eq!(OptionalVersion::NONE.to_string(), "");
+        assert_eq!(
+            " ".parse::<OptionalVersion>(),
+            Err(" ".parse::<DebianVersion>().unwrap_err())
+        );
+    }
+
+    #[test]
+    fn empty_is_earliest() {
+        let mut versions = [
+            optional("1.0"),
+            optional(""),
+            optional("0~~"),
+            optional("0"),
+        ];
+        versions.sort();
+
+        assert_eq!(
+            versions.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["", "0~~", "0", "1.0"]
+        );
+        assert_eq!(optional(""), optional("<unknown>"));
+        assert!(optional("") < optional("0~~~"));
+        assert_eq!(optional("1.01"), "1.1".parse::<DebianVersion>().unwrap());
+    }
+
+    #[test]
+    fn empty_is_latest() {
+        let empty = OptionalVersion::NONE;
+        let version = optional("99:1.0");
+
+        assert_eq!(
+            empty.cmp_with(&version, EmptyVersion::Latest),
+            Ordering::Greater
+        );
+        assert_eq!(
+            version.cmp_with(&empty, EmptyVersion::Latest),
+            Ordering::Less
+        );
+        assert_eq!(
+            empty.cmp_with(&empty, EmptyVersion::Latest),
+            Ordering::Equal
+        );
+        assert_eq!(
+            version.cmp_with(&optional("1.0"), EmptyVersion::Latest),
+            Ordering::Greater
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Options controlling how strictly versions are parsed.
+//!
+//! By default versions must follow the Debian Policy Manual. Third-party repositories contain
+//! versions that violate it, but are still installed by `dpkg` (with a warning) or compared by
+//! APT, which doesn't validate versions at all. [`ParseOptions`](crate::options::ParseOptions)
+//! turns the corresponding errors into warnings.
+
+use crate::error::DebianVersionError;
+use crate::{parse_components, DebianVersionRef, Result};
+
+/// Flags relaxing the validation of versions. The APT-compatible relaxations build on the
+/// `dpkg`-compatible ones: setting any of `allow_whitespace`, `lax_epoch` or
+/// `allow_empty_components` requires `dpkg_warnings`, otherwise parsing fails with
+/// [`DebianVersionError::InvalidFlags`](crate::error::DebianVersionError::InvalidFlags).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Ignore leading and trailing whitespace, like `dpkg` and APT do.
+    pub trim_whitespace: bool,
+    /// Report problems `dpkg` only warns about as warnings: an upstream version not starting
+    /// with a digit and invalid characters other than whitespace.
+    pub dpkg_warnings: bool,
+    /// Report whitespace within the version as a warning as well.
+    pub allow_whitespace: bool,
+    /// Treat a non-numeric prefix followed by `:` as part of the upstream version instead of an
+    /// invalid epoch, like APT does.
+    pub lax_epoch: bool,
+    /// Report an empty upstream version or Debian revision as a warning.
+    pub allow_empty_components: bool,
+}
+
+impl ParseOptions {
+    /// Enforces the Debian Policy Manual, like [`DebianVersion::from_str`](crate::DebianVersion).
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Accepts what `dpkg` accepts, reporting what it warns about as warnings.
+    pub fn dpkg() -> Self {
+        Self {
+            trim_whitespace: true,
+            dpkg_warnings: true,
+            ..Self::default()
+        }
+    }
+
+    /// Accepts every non-empty string, like APT's version comparison does. Everything violating
+    /// the Debian Policy Manual is reported as a warning.
+    pub fn apt() -> Self {
+        Self {
+            trim_whitespace: true,
+            dpkg_warnings: true,
+            allow_whitespace: true,
+            lax_epoch: true,
+            allow_empty_components: true,
+        }
+    }
+
+    /// Parses `s` into a [`DebianVersionRef`](crate::DebianVersionRef), returning it together
+    /// with the tolerated problems. Error locations refer to `s` before trimming.
+    pub fn parse<'a>(&self, s: &'a str) -> Result<(DebianVersionRef<'a>, Vec<DebianVersionError>)> {
+        if !self.dpkg_warnings
+            && (self.allow_whitespace || self.lax_epoch || self.allow_empty_components)
+        {
+            return Err(DebianVersionError::InvalidFlags);
+        }
+
+        let (input, offset) = if self.trim_whitespace {
+            (s.trim(), s.len() - s.trim_start().len())
+        } else {
+            (s, 0)
+        };
+
+        let mut warnings = Vec::new();
+        let version = parse_components(input, self, &mut warnings)
+            .map_err(|error| error.relocate(s, offset))?;
+
+        Ok((
+            version,
+            warnings
+                .into_iter()
+                .map(|warning| warning.relocate(s, offset))
+                .collect(),
+        ))
+    }
+
+    /// Returns `true` if `error` is reported as a warning instead of failing.
+    pub(crate) fn tolerates(&self, error: &DebianVersionError) -> bool {
+        match *error {
+            DebianVersionError::UpstreamStartWithDigit(_)
+            | DebianVersionError::UpstreamInvalidCharacters(_)
+            | DebianVersionError::RevisionInvalidCharacters(_) => self.dpkg_warnings,
+            DebianVersionError::EmptyUpstream(_) | DebianVersionError::EmptyRevision(_) => {
+                self.allow_empty_components
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DebianVersion;
+    use pretty_assertions::assert_eq;
+
+    fn parse(s: &str, options: ParseOptions) -> Result<(String, usize)> {
+        DebianVersion::parse_with(s, &options)
+            .map(|(version, warnings)| (version.to_string(), warnings.len()))
+    }
+
+    #[test]
+    fn modes() {
+        let cases = [
+            // input, strict, dpkg, apt
+            ("1.0-1", true, true, true),
+            (" 1.0-1\n", false, true, true),
+            ("v1.0_1-a_b", false, true, true),
+            ("1.0 1", false, false, true),
+            ("x:1.0", false, false, true),
+            ("1.0-", false, false, true),
+            ("1:", false, false, true),
+            ("", false, false, false),
+        ];
+
+        for (input, strict, dpkg, apt) in cases {
+            assert_eq!(
+                parse(input, ParseOptions::strict()).is_ok(),
+                strict,
+                "{:?}",
+                input
+            );
+            assert_eq!(
+                parse(input, ParseOptions::dpkg()).is_ok(),
+                dpkg,
+                "{:?}",
+                input
+            );
+            assert_eq!(
+                parse(input, ParseOptions::apt()).is_ok(),
+                apt,
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn warnings() {
+        assert_eq!(
+            parse(" 1.0-1\n", ParseOptions::dpkg()),
+            Ok(("1.0-1".to_string(), 0))
+        );
+        assert_eq!(
+            parse("x:1.0", ParseOptions::apt()),
+            Ok(("x:1.0".to_string(), 1))
+        );
+
+        let (version, warnings) = ParseOptions::apt().parse("  1.0 a-").unwrap();
+        assert_eq!(version.upstream_version, "1.0 a");
+        assert_eq!(version.debian_revision, Some(""));
+        assert_eq!(warnings[0].span(), Some(5..6));
+        assert_eq!(warnings[0].location().unwrap().input, "  1.0 a-");
+        assert!(matches!(warnings[1], DebianVersionError::EmptyRevision(_)));
+
+        let error = ParseOptions::dpkg().parse(" 1.0 1").unwrap_err();
+        assert_eq!(error.span(), Some(4..5));
+    }
+
+    #[test]
+    fn invalid_flags() {
+        let options = ParseOptions {
+            lax_epoch: true,
+            ..ParseOptions::strict()
+        };
+
+        assert_eq!(
+            options.parse("1.0").unwrap_err(),
+            DebianVersionError::InvalidFlags
+        );
+        assert_eq!(
+            "Invalid flag combination.",
+            DebianVersionError::InvalidFlags.to_string()
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Reading of APT `Packages` indexes into a structure keyed by package name, with the available
+//! versions of every package sorted.
+//!
+//! Indexes compressed with gzip or xz can be read with the `gzip` and `xz` features enabled.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::control::{relations, required, version};
+use crate::deb822::{self, Paragraph};
+use crate::error::{Deb822Error, IndexError};
+use crate::range::VersionRange;
+use crate::relation::{Dependency, RelationField, VersionRelation};
+use crate::DebianVersion;
+
+/// A binary package of a `Packages` index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BinaryPackage {
+    pub package: String,
+    pub version: DebianVersion,
+    pub architecture: String,
+    /// The complete paragraph, for access to the remaining fields.
+    pub paragraph: Paragraph,
+}
+
+impl BinaryPackage {
+    /// Parses the relationship field called `name`, e.g. `Depends`. Missing fields are empty.
+    pub fn relations(&self, name: &str) -> Result<RelationField, Deb822Error> {
+        relations(&self.paragraph, name)
+    }
+}
+
+impl TryFrom<Paragraph> for BinaryPackage {
+    type Error = Deb822Error;
+
+    fn try_from(paragraph: Paragraph) -> Result<Self, Self::Error> {
+        Ok(Self {
+            package: required(&paragraph, "Package")?,
+            version: version(&paragraph)?.ok_or(Deb822Error::MissingField("Version"))?,
+            architecture: required(&paragraph, "Architecture")?,
+            paragraph,
+        })
+    }
+}
+
+/// The packages of one or more `Packages` indexes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackagesIndex {
+    /// The packages by name, sorted by version in ascending order.
+    packages: BTreeMap<String, Vec<BinaryPackage>>,
+}
+
+impl PackagesIndex {
+    /// Reads an uncompressed `Packages` index.
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, IndexError> {
+        let mut index = Self::default();
+        index.extend(reader)?;
+
+        Ok(index)
+    }
+
+    /// Reads a `Packages` index from a file, decompressing it according to the extension.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, IndexError> {
+        Self::read(open_index(path.as_ref())?)
+    }
+
+    /// Adds the packages of another uncompressed `Packages` index, e.g. of another component or
+    /// architecture.
+    pub fn extend<R: BufRead>(&mut self, reader: R) -> Result<(), IndexError> {
+        for paragraph in deb822::paragraphs(reader) {
+            let (line, paragraph) = paragraph?;
+            self.insert(
+                BinaryPackage::try_from(paragraph)
+                    .map_err(|error| IndexError::Paragraph(line, error))?,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Adds a package, keeping the versions of the package sorted.
+    pub fn insert(&mut self, package: BinaryPackage) {
+        let versions = self.packages.entry(package.package.clone()).or_default();
+        let position = versions.partition_point(|existing| existing.version <= package.version);
+        versions.insert(position, package);
+    }
+
+    /// Returns the names of all packages in alphabetical order.
+    pub fn package_names(&self) -> impl Iterator<Item = &str> {
+        self.packages.keys().map(String::as_str)
+    }
+
+    /// Returns the number of distinct package names.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Returns all entries of the package called `package`, sorted by version in ascending order.
+    /// The same version may appear once per architecture.
+    pub fn versions(&self, package: &str) -> &[BinaryPackage] {
+        self.packages.get(package).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the newest version of the package called `package`.
+    pub fn newest(&self, package: &str) -> Option<&BinaryPackage> {
+        self.versions(package).last()
+    }
+
+    /// Returns the newest version of the package called `package` satisfying `relation`.
+    pub fn newest_satisfying(
+        &self,
+        package: &str,
+        relation: &VersionRelation,
+    ) -> Option<&BinaryPackage> {
+        self.versions(package)
+            .iter()
+            .rev()
+            .find(|candidate| relation.satisfied_by(&candidate.version))
+    }
+
+    /// Returns the newest package satisfying `dependency`, ignoring architecture restrictions and
+    /// build profiles.
+    pub fn resolve(&self, dependency: &Dependency) -> Option<&BinaryPackage> {
+        match dependency.version {
+            Some(ref relation) => self.newest_satisfying(&dependency.package, relation),
+            None => self.newest(&dependency.package),
+        }
+    }
+
+    /// Returns all versions of the package called `package` within `range`, in ascending order.
+    pub fn in_range<'a>(
+        &'a self,
+        package: &str,
+        range: &'a VersionRange,
+    ) -> impl Iterator<Item = &'a BinaryPackage> {
+        self.versions(package)
+            .iter()
+            .filter(move |candidate| range.contains(&candidate.version))
+    }
+}
+
+impl FromStr for PackagesIndex {
+    type Err = IndexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::read(s.as_bytes())
+    }
+}
+
+/// Opens an index file, decompressing it if the extension is `.gz` or `.xz`.
+pub fn open_index(path: &Path) -> Result<Box<dyn BufRead>, IndexError> {
+    let file = File::open(path)?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gz") => gzip(file),
+        Some("xz") => xz(file),
+        _ => Ok(Box::new(BufReader::new(file))),
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(file: File) -> Result<Box<dyn BufRead>, IndexError> {
+    Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(
+        file,
+    ))))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip(_file: File) -> Result<Box<dyn BufRead>, IndexError> {
+    Err(IndexError::UnsupportedCompression("gzip"))
+}
+
+#[cfg(feature = "xz")]
+fn xz(file: File) -> Result<Box<dyn BufRead>, IndexError> {
+    Ok(Box::new(BufReader::new(
+        xz2::read::XzDecoder::new_multi_decoder(file),
+    )))
+}
+
+#[cfg(not(feature = "xz"))]
+fn xz(_file: File) -> Result<Box<dyn BufRead>, IndexError> {
+    Err(IndexError::UnsupportedCompression("xz"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const PACKAGES: &str = "\
+Package: libfoo1
+Version: 2.1-1
+Architecture: amd64
+Depends: libc6 (>= 2.34)
+
+Package: libfoo1
+Version: 2.0-3
+Architecture: amd64
+
+Package: libfoo1
+Version: 2.10~rc1-1
+Architecture: amd64
+
+Package: libfoo1
+Version: 1:1.0-1
+Architecture: amd64
+
+Package: bar
+Version: 1.0
+Architecture: all
+";
+
+    fn versions<'a>(packages: impl IntoIterator<Item = &'a BinaryPackage>) -> Vec<String> {
+        packages
+            .into_iter()
+            .map(|package| package.version.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn sorted_versions() {
+        let index = PACKAGES.parse::<PackagesIndex>().unwrap();
+
+        assert_eq!(
+            index.package_names().collect::<Vec<_>>(),
+            ["bar", "libfoo1"]
+        );
+        assert_eq!(
+            versions(index.versions("libfoo1")),
+            ["2.0-3", "2.1-1", "2.10~rc1-1", "1:1.0-1"]
+        );
+        assert!(index.versions("missing").is_empty());
+        assert_eq!(
+            index.versions("libfoo1")[1]
+                .relations("Depends")
+                .unwrap()
+                .to_string(),
+            "libc6 (>= 2.34)"
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let index = PACKAGES.parse::<PackagesIndex>().unwrap();
+        let newest = |relation: &str| {
+            index
+                .newest_satisfying("libfoo1", &relation.parse().unwrap())
+                .map(|package| package.version.to_string())
+        };
+
+        assert_eq!(newest(">= 2.1").as_deref(), Some("1:1.0-1"));
+        assert_eq!(newest("<< 2.10").as_deref(), Some("2.10~rc1-1"));
+        assert_eq!(newest("<< 2.0").as_deref(), None);
+
+        let range = "(>= 2.1), (<< 1:0)".parse::<VersionRange>().unwrap();
+        assert_eq!(
+            versions(index.in_range("libfoo1", &range)),
+            ["2.1-1", "2.10~rc1-1"]
+        );
+
+        let dependency = "libfoo1 (<< 2.1)".parse::<Dependency>().unwrap();
+        assert_eq!(
+            index.resolve(&dependency).map(|package| &package.version),
+            Some(&"2.0-3".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = "Package: a\nVersion: 1\nArchitecture: all\n\nPackage: b\nArchitecture: all\n"
+            .parse::<PackagesIndex>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            IndexError::Paragraph(5, Deb822Error::MissingField("Version"))
+        ));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_compressed() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("versian-{}-Packages.gz", std::process::id()));
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&path).unwrap(), Default::default());
+        encoder.write_all(PACKAGES.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let index = PackagesIndex::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(index.unwrap(), PACKAGES.parse().unwrap());
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_compressed() {
+        use std::io::Write;
+
+        let path = std::env::temp_dir().join(format!("versian-{}-Packages.xz", std::process::id()));
+        let mut encoder = xz2::write::XzEncoder::new(File::create(&path).unwrap(), 6);
+        encoder.write_all(PACKAGES.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let index = PackagesIndex::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(index.unwrap(), PACKAGES.parse().unwrap());
+    }
+}
@@ -0,0 +1,280 @@
+//! Translation of Python package versions as specified in PEP 440 into Debian versions, following
+//! the conventions of pybuild and py2dsp where they preserve the order of versions:
+//!
+//! | PEP 440        | Debian          |
+//! |----------------|-----------------|
+//! | `1!2.1`        | `1:2.1`         |
+//! | `1.1.dev1`     | `1.1~~dev1`     |
+//! | `1.1a1`        | `1.1~a1`        |
+//! | `1.1rc1.dev2`  | `1.1~rc1~dev2`  |
+//! | `1.1.post1`    | `1.1+post1`     |
+//! | `1.1+cu118`    | `1.1+~cu118`    |
+//! | `1.0.0`        | `1`             |
+//!
+//! PEP 440 ignores trailing zeros of the release, so that `1.0` equals `1.0.0`, while Debian sorts
+//! `1.0` first. Trailing zeros are therefore removed, keeping the first number, so that for
+//! example `1.0.post1` and `1.0.0` become `1+post1` and `1`, ordered as in PEP 440.
+//!
+//! Segments of local versions compare like Debian versions, which differs from PEP 440 when a
+//! segment of letters is compared with a number.
+
+use crate::error::InvalidPep440Version;
+use crate::DebianVersion;
+
+const SEPARATORS: [char; 3] = ['.', '-', '_'];
+
+impl DebianVersion {
+    /// Translates a PEP 440 version into a Debian version without revision. Spellings that PEP
+    /// 440 normalizes, e.g. `v1.0-ALPHA.1` or `1.0-1` for `1.0a1` and `1.0.post1`, are accepted.
+    pub fn from_pep440(s: &str) -> Result<DebianVersion, InvalidPep440Version> {
+        let invalid = || InvalidPep440Version(s.to_string());
+        let normalized = s.trim().to_ascii_lowercase();
+        let mut rest = normalized.strip_prefix('v').unwrap_or(&normalized);
+
+        let epoch = match rest.split_once('!') {
+            Some((epoch, release)) => {
+                rest = release;
+                if epoch.is_empty() || !epoch.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                match epoch.parse().map_err(|_| invalid())? {
+                    0 => None,
+                    epoch => Some(epoch),
+                }
+            }
+            None => None,
+        };
+
+        let mut release = Vec::new();
+        loop {
+            release.push(take_number(&mut rest).ok_or_else(invalid)?);
+            match rest.strip_prefix('.') {
+                Some(after) if after.starts_with(|c: char| c.is_ascii_digit()) => rest = after,
+                _ => break,
+            }
+        }
+
+        let pre = take_labelled(
+            &mut rest,
+            &["preview", "alpha", "beta", "pre", "rc", "a", "b", "c"],
+        );
+        let post = match rest.strip_prefix('-') {
+            Some(mut after) if after.starts_with(|c: char| c.is_ascii_digit()) => {
+                let number = take_number(&mut after);
+                rest = after;
+                number
+            }
+            _ => take_labelled(&mut rest, &["post", "rev", "r"]).map(|(_, number)| number),
+        };
+        let dev = take_labelled(&mut rest, &["dev"]).map(|(_, number)| number);
+
+        let local = match rest.strip_prefix('+') {
+            Some(local) => {
+                let segments = local.split(SEPARATORS).collect::<Vec<_>>();
+                if segments.iter().any(|segment| {
+                    segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_alphanumeric())
+                }) {
+                    return Err(invalid());
+                }
+                rest = "";
+                Some(segments.join("."))
+            }
+            None => None,
+        };
+
+        if !rest.is_empty() {
+            return Err(invalid());
+        }
+
+        // Trailing zeros don't take part in the order of PEP 440.
+        while release.len() > 1 && release.last().is_some_and(|number| number == "0") {
+            release.pop();
+        }
+
+        let mut upstream_version = release.join(".");
+        if let Some((label, number)) = &pre {
+            let label = match *label {
+                "alpha" | "a" => "a",
+                "beta" | "b" => "b",
+                _ => "rc",
+            };
+            upstream_version.push_str(&format!("~{}{}", label, number));
+        }
+        if let Some(number) = &post {
+            upstream_version.push_str(&format!("+post{}", number));
+        }
+        if let Some(number) = dev {
+            // A development release of a final release sorts before its pre-releases.
+            let tilde = if pre.is_none() && post.is_none() {
+                "~~"
+            } else {
+                "~"
+            };
+            upstream_version.push_str(&format!("{}dev{}", tilde, number));
+        }
+        if let Some(local) = local {
+            upstream_version.push_str(&format!("+~{}", local));
+        }
+
+        Ok(DebianVersion {
+            epoch,
+            upstream_version,
+            debian_revision: None,
+        })
+    }
+}
+
+/// Removes a run of digits from the start of `rest` and returns it without leading zeros.
+fn take_number(rest: &mut &str) -> Option<String> {
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    if end == 0 {
+        return None;
+    }
+
+    let (digits, after) = rest.split_at(end);
+    *rest = after;
+    let significant = digits.trim_start_matches('0');
+    Some(
+        if significant.is_empty() {
+            "0"
+        } else {
+            significant
+        }
+        .to_string(),
+    )
+}
+
+/// Removes an optionally separated label and number, e.g. `-rc.1`, from the start of `rest`.
+/// The number defaults to `0`.
+fn take_labelled(rest: &mut &str, labels: &[&'static str]) -> Option<(&'static str, String)> {
+    let after = rest.strip_prefix(SEPARATORS).unwrap_or(rest);
+    let label = labels.iter().find(|label| after.starts_with(*label))?;
+    let mut after = &after[label.len()..];
+
+    let mut number_rest = after.strip_prefix(SEPARATORS).unwrap_or(after);
+    let number = match take_number(&mut number_rest) {
+        Some(number) => {
+            after = number_rest;
+            number
+        }
+        None => "0".to_string(),
+    };
+
+    *rest = after;
+    Some((label, number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn translate(s: &str) -> String {
+        DebianVersion::from_pep440(s).unwrap().to_string()
+    }
+
+    #[test]
+    fn translations() {
+        for (pep440, debian) in [
+            ("1!2.1", "1:2.1"),
+            ("0!2.1", "2.1"),
+            ("1.1.dev1", "1.1~~dev1"),
+            ("1.1a1", "1.1~a1"),
+            ("1.1rc1.dev2", "1.1~rc1~dev2"),
+            ("1.1.post1", "1.1+post1"),
+            ("1.1.post1.dev3", "1.1+post1~dev3"),
+            ("1.1+cu118", "1.1+~cu118"),
+            ("2.1b3.post1+ubuntu-1", "2.1~b3+post1+~ubuntu.1"),
+        ] {
+            assert_eq!(translate(pep440), debian, "{}", pep440);
+        }
+    }
+
+    #[test]
+    fn normalization() {
+        for (pep440, debian) in [
+            (" v1.1-ALPHA.1 ", "1.1~a1"),
+            ("1.1c1", "1.1~rc1"),
+            ("1.1pre2", "1.1~rc2"),
+            ("1.1-preview_3", "1.1~rc3"),
+            ("1.1b", "1.1~b0"),
+            ("1.1-1", "1.1+post1"),
+            ("1.1rev", "1.1+post0"),
+            ("1.1_r2", "1.1+post2"),
+            ("01.002", "1.2"),
+            ("1.1.dev", "1.1~~dev0"),
+            ("1.0", "1"),
+            ("1.0.0", "1"),
+            ("0.0", "0"),
+            ("1.0.1.0", "1.0.1"),
+            ("2.0.0rc1", "2~rc1"),
+            ("1!1.0.post1", "1:1+post1"),
+        ] {
+            assert_eq!(translate(pep440), debian, "{}", pep440);
+        }
+    }
+
+    #[test]
+    fn preserves_order() {
+        // The example of PEP 440, except for `1.0+5`, whose local version sorts after
+        // `1.0+abc.7` in PEP 440 but before it in Debian.
+        let ordered = [
+            "1.dev0",
+            "1.0.dev456",
+            "1.0a1",
+            "1.0a2.dev456",
+            "1.0a12.dev456",
+            "1.0a12",
+            "1.0b1.dev456",
+            "1.0b2",
+            "1.0b2.post345.dev456",
+            "1.0b2.post345",
+            "1.0rc1.dev456",
+            "1.0rc1",
+            "1.0",
+            "1.0+abc.5",
+            "1.0+abc.7",
+            "1.0.post456.dev34",
+            "1.0.post456",
+            "1.0.15",
+            "1.1.dev1",
+            "1!0.1",
+        ]
+        .map(|s| DebianVersion::from_pep440(s).unwrap());
+
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        for version in ordered {
+            assert_eq!(version.to_string().parse::<DebianVersion>(), Ok(version));
+        }
+    }
+
+    #[test]
+    fn trailing_zeros() {
+        let version = |s| DebianVersion::from_pep440(s).unwrap();
+
+        assert_eq!(version("1.0"), version("1.0.0"));
+        assert!(version("1.0.0") < version("1.0.post1"));
+        assert!(version("1.0.0") < version("1.0+cu118"));
+        assert!(version("1.0.0a1") < version("1.0"));
+        assert!(version("1.0.post1") < version("1.0.1"));
+        assert!(version("1.0+cu118") < version("1.0.0.1"));
+    }
+
+    #[test]
+    fn invalid() {
+        for invalid in [
+            "", "a1", "1.0foo", "1.0+", "1.0+a..b", "!1.0", "x!1.0", "1.0.", "1.0-",
+        ] {
+            assert_eq!(
+                DebianVersion::from_pep440(invalid),
+                Err(InvalidPep440Version(invalid.to_string())),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Sets of [`DebianVersion`](crate::DebianVersion)s described by their bounds.
+//!
+//! A [`VersionInterval`](crate::range::VersionInterval) is a single contiguous interval, a
+//! [`VersionRange`](crate::range::VersionRange) is a union of disjoint intervals and therefore
+//! closed under intersection and union.
+
+use std::{cmp::Ordering, ops::Bound, str::FromStr};
+
+use crate::error::RelationError;
+use crate::relation::{RelationOperator, VersionRelation};
+use crate::DebianVersion;
+
+/// A contiguous interval of [`DebianVersion`](crate::DebianVersion)s.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInterval {
+    pub lower: Bound<DebianVersion>,
+    pub upper: Bound<DebianVersion>,
+}
+
+impl VersionInterval {
+    /// Returns the interval between the `lower` and `upper` bound.
+    #[inline]
+    pub fn new(lower: Bound<DebianVersion>, upper: Bound<DebianVersion>) -> Self {
+        Self { lower, upper }
+    }
+
+    /// Returns the interval containing every version.
+    #[inline]
+    pub fn full() -> Self {
+        Self::new(Bound::Unbounded, Bound::Unbounded)
+    }
+
+    /// Returns the interval containing exactly `version`.
+    #[inline]
+    pub fn exactly(version: DebianVersion) -> Self {
+        Self::new(Bound::Included(version.clone()), Bound::Included(version))
+    }
+
+    /// Returns `true` if `version` lies within the interval.
+    pub fn contains(&self, version: &DebianVersion) -> bool {
+        let above_lower = match self.lower {
+            Bound::Included(ref lower) => version >= lower,
+            Bound::Excluded(ref lower) => version > lower,
+            Bound::Unbounded => true,
+        };
+        let below_upper = match self.upper {
+            Bound::Included(ref upper) => version <= upper,
+            Bound::Excluded(ref upper) => version < upper,
+            Bound::Unbounded => true,
+        };
+
+        above_lower && below_upper
+    }
+
+    /// Returns `true` if no version lies within the interval.
+    pub fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+            (Bound::Included(lower), Bound::Included(upper)) => lower > upper,
+            (Bound::Included(lower), Bound::Excluded(upper))
+            | (Bound::Excluded(lower), Bound::Included(upper))
+            | (Bound::Excluded(lower), Bound::Excluded(upper)) => lower >= upper,
+        }
+    }
+
+    /// Returns the interval of versions contained in both `self` and `other`.
+    pub fn intersect(&self, other: &Self) -> Self {
+        let lower = match cmp_lower(&self.lower, &other.lower) {
+            Ordering::Less => other.lower.clone(),
+            _ => self.lower.clone(),
+        };
+        let upper = match cmp_upper(&self.upper, &other.upper) {
+            Ordering::Greater => other.upper.clone(),
+            _ => self.upper.clone(),
+        };
+
+        Self::new(lower, upper)
+    }
+}
+
+impl From<&VersionRelation> for VersionInterval {
+    fn from(relation: &VersionRelation) -> Self {
+        let version = relation.version.clone();
+
+        match relation.operator {
+            RelationOperator::StrictlyEarlier => {
+                Self::new(Bound::Unbounded, Bound::Excluded(version))
+            }
+            RelationOperator::EarlierOrEqual | RelationOperator::DeprecatedEarlierOrEqual => {
+                Self::new(Bound::Unbounded, Bound::Included(version))
+            }
+            RelationOperator::Exactly => Self::exactly(version),
+            RelationOperator::LaterOrEqual | RelationOperator::DeprecatedLaterOrEqual => {
+                Self::new(Bound::Included(version), Bound::Unbounded)
+            }
+            RelationOperator::StrictlyLater => {
+                Self::new(Bound::Excluded(version), Bound::Unbounded)
+            }
+        }
+    }
+}
+
+/// A set of [`DebianVersion`](crate::DebianVersion)s, stored as sorted, disjoint and non-adjacent
+/// [`VersionInterval`](crate::range::VersionInterval)s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionRange {
+    intervals: Vec<VersionInterval>,
+}
+
+impl VersionRange {
+    /// Returns the range containing no version at all.
+    #[inline]
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Returns the range containing every version.
+    #[inline]
+    pub fn full() -> Self {
+        Self::from(VersionInterval::full())
+    }
+
+    /// Builds the range of versions satisfying all of the given relations, e.g. `(>= 1.0)` and
+    /// `(<< 2.0~)`.
+    pub fn from_relations<'a, I>(relations: I) -> Self
+    where
+        I: IntoIterator<Item = &'a VersionRelation>,
+    {
+        relations.into_iter().fold(Self::full(), |range, relation| {
+            range.intersect(&Self::from(relation))
+        })
+    }
+
+    /// Returns the disjoint intervals making up the range, in ascending order.
+    #[inline]
+    pub fn intervals(&self) -> &[VersionInterval] {
+        &self.intervals
+    }
+
+    /// Returns `true` if `version` lies within the range.
+    pub fn contains(&self, version: &DebianVersion) -> bool {
+        self.intervals
+            .iter()
+            .any(|interval| interval.contains(version))
+    }
+
+    /// Returns `true` if no version lies within the range.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns the range of versions contained in both `self` and `other`.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::normalized(
+            self.intervals
+                .iter()
+                .flat_map(|a| other.intervals.iter().map(move |b| a.intersect(b)))
+                .collect(),
+        )
+    }
+
+    /// Returns the range of versions contained in `self`, `other` or both.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::normalized(
+            self.intervals
+                .iter()
+                .chain(other.intervals.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Drops empty intervals, sorts the remaining ones and merges those that overlap or touch.
+    fn normalized(mut intervals: Vec<VersionInterval>) -> Self {
+        intervals.retain(|interval| !interval.is_empty());
+        intervals.sort_by(|a, b| cmp_lower(&a.lower, &b.lower));
+
+        let mut merged: Vec<VersionInterval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if connected(&last.upper, &interval.lower) => {
+                    if cmp_upper(&interval.upper, &last.upper) == Ordering::Greater {
+                        last.upper = interval.upper;
+                    }
+                }
+                _ => merged.push(interval),
+            }
+        }
+
+        Self { intervals: merged }
+    }
+}
+
+impl From<VersionInterval> for VersionRange {
+    fn from(interval: VersionInterval) -> Self {
+        Self::normalized(vec![interval])
+    }
+}
+
+impl From<&VersionRelation> for VersionRange {
+    fn from(relation: &VersionRelation) -> Self {
+        Self::from(VersionInterval::from(relation))
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = RelationError;
+
+    /// Parses a comma separated conjunction of version relations such as `(>= 1.0), (<< 2.0~)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let relations = s
+            .split(',')
+            .map(|relation| {
+                relation
+                    .trim()
+                    .strip_prefix('(')
+                    .and_then(|relation| relation.strip_suffix(')'))
+                    .ok_or(RelationError::UnterminatedVersion)?
+                    .parse::<VersionRelation>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::from_relations(&relations))
+    }
+}
+
+/// Orders lower bounds by the first version they admit.
+fn cmp_lower(a: &Bound<DebianVersion>, b: &Bound<DebianVersion>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(a), Bound::Included(b)) | (Bound::Excluded(a), Bound::Excluded(b)) => {
+            a.cmp(b)
+        }
+        (Bound::Included(a), Bound::Excluded(b)) => a.cmp(b).then(Ordering::Less),
+        (Bound::Excluded(a), Bound::Included(b)) => a.cmp(b).then(Ordering::Greater),
+    }
+}
+
+/// Orders upper bounds by the last version they admit.
+fn cmp_upper(a: &Bound<DebianVersion>, b: &Bound<DebianVersion>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(a), Bound::Included(b)) | (Bound::Excluded(a), Bound::Excluded(b)) => {
+            a.cmp(b)
+        }
+        (Bound::Included(a), Bound::Excluded(b)) => a.cmp(b).then(Ordering::Greater),
+        (Bound::Excluded(a), Bound::Included(b)) => a.cmp(b).then(Ordering::Less),
+    }
+}
+
+/// Returns `true` if an interval ending at `upper` and one starting at `lower` leave no gap.
+fn connected(upper: &Bound<DebianVersion>, lower: &Bound<DebianVersion>) -> bool {
+    match (upper, lower) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+        (Bound::Excluded(upper), Bound::Excluded(lower)) => lower < upper,
+        (Bound::Included(upper), Bound::Included(lower))
+        | (Bound::Included(upper), Bound::Excluded(lower))
+        | (Bound::Excluded(upper), Bound::Included(lower)) => lower <= upper,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn version(s: &str) -> DebianVersion {
+        s.parse().unwrap()
+    }
+
+    fn range(s: &str) -> VersionRange {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn from_relations() {
+        let range = range("(>= 1.0), (<< 2.0~)");
+
+        assert!(range.contains(&version("1.0")));
+        assert!(range.contains(&version("1.9.9-3")));
+        assert!(!range.contains(&version("2.0~rc1")));
+        assert!(!range.contains(&version("1.0~beta")));
+        assert!(range.contains(&version("1.0-0")));
+    }
+
+    #[test]
+    fn intersect_detects_conflicts() {
+        let depends = range("(>= 1.0)");
+        let breaks = range("(<< 1.0)");
+
+        assert!(depends.intersect(&breaks).is_empty());
+        assert!(!depends.intersect(&range("(<= 1.0)")).is_empty());
+        assert!(range("(>> 2.0), (<< 2.0)").is_empty());
+        assert!(range("(= 1.0), (<< 1.0)").is_empty());
+    }
+
+    #[test]
+    fn union_merges_adjacent_intervals() {
+        let union = range("(<< 1.0)").union(&range("(>= 1.0), (<= 2.0)"));
+
+        assert_eq!(union, range("(<= 2.0)"));
+        assert_eq!(union.intervals().len(), 1);
+    }
+
+    #[test]
+    fn union_keeps_gaps() {
+        let union = range("(<< 1.0)").union(&range("(>> 1.0)"));
+
+        assert_eq!(union.intervals().len(), 2);
+        assert!(!union.contains(&version("1.0")));
+        assert!(union.contains(&version("0.9")));
+        assert!(union.contains(&version("1.1")));
+        assert_eq!(union.union(&range("(= 1.0)")), VersionRange::full());
+    }
+}
@@ -0,0 +1,587 @@
+//! Parsing of Debian package relationship fields such as `Depends`, `Pre-Depends`, `Breaks` or
+//! `Build-Depends`, as specified in the Debian Policy Manual, section 7.1.
+//!
+//! A field is a comma separated list of [`Relation`](crate::relation::Relation)s, all of which
+//! must be satisfied. A relation is a `|` separated list of alternative
+//! [`Dependency`](crate::relation::Dependency)s, of which one must be satisfied.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::error::RelationError;
+use crate::DebianVersion;
+
+/// The operator of a [`VersionRelation`](crate::relation::VersionRelation).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RelationOperator {
+    /// `<<`
+    StrictlyEarlier,
+    /// `<=`
+    EarlierOrEqual,
+    /// `=`
+    Exactly,
+    /// `>=`
+    LaterOrEqual,
+    /// `>>`
+    StrictlyLater,
+    /// `<`, a deprecated spelling of `<=` that dpkg still accepts with a warning.
+    DeprecatedEarlierOrEqual,
+    /// `>`, a deprecated spelling of `>=` that dpkg still accepts with a warning.
+    DeprecatedLaterOrEqual,
+}
+
+impl RelationOperator {
+    /// Returns the textual representation of the operator as used in relationship fields.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationOperator::StrictlyEarlier => "<<",
+            RelationOperator::EarlierOrEqual => "<=",
+            RelationOperator::Exactly => "=",
+            RelationOperator::LaterOrEqual => ">=",
+            RelationOperator::StrictlyLater => ">>",
+            RelationOperator::DeprecatedEarlierOrEqual => "<",
+            RelationOperator::DeprecatedLaterOrEqual => ">",
+        }
+    }
+
+    /// Returns `true` for the deprecated `<` and `>` spellings, which should be reported as a
+    /// warning.
+    #[inline]
+    pub fn is_deprecated(&self) -> bool {
+        matches!(
+            self,
+            RelationOperator::DeprecatedEarlierOrEqual | RelationOperator::DeprecatedLaterOrEqual
+        )
+    }
+
+    /// Returns `true` if a candidate version that compares to the target version as `ordering`
+    /// satisfies the operator.
+    #[inline]
+    pub fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            RelationOperator::StrictlyEarlier => ordering == Ordering::Less,
+            RelationOperator::EarlierOrEqual | RelationOperator::DeprecatedEarlierOrEqual => {
+                ordering != Ordering::Greater
+            }
+            RelationOperator::Exactly => ordering == Ordering::Equal,
+            RelationOperator::LaterOrEqual | RelationOperator::DeprecatedLaterOrEqual => {
+                ordering != Ordering::Less
+            }
+            RelationOperator::StrictlyLater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for RelationOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for RelationOperator {
+    type Err = RelationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "<<" => Ok(RelationOperator::StrictlyEarlier),
+            "<=" => Ok(RelationOperator::EarlierOrEqual),
+            "=" => Ok(RelationOperator::Exactly),
+            ">=" => Ok(RelationOperator::LaterOrEqual),
+            ">>" => Ok(RelationOperator::StrictlyLater),
+            "<" => Ok(RelationOperator::DeprecatedEarlierOrEqual),
+            ">" => Ok(RelationOperator::DeprecatedLaterOrEqual),
+            _ => Err(RelationError::InvalidOperator),
+        }
+    }
+}
+
+/// A version restriction such as `(>= 2.34)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRelation {
+    pub operator: RelationOperator,
+    pub version: DebianVersion,
+}
+
+impl VersionRelation {
+    /// Returns `true` if `version` satisfies the relation, e.g. `2.35` satisfies `(>= 2.34)`.
+    #[inline]
+    pub fn satisfied_by(&self, version: &DebianVersion) -> bool {
+        self.operator.matches(version.cmp(&self.version))
+    }
+
+    /// Returns `true` if the relation uses one of the deprecated `<` and `>` operators.
+    #[inline]
+    pub fn is_deprecated(&self) -> bool {
+        self.operator.is_deprecated()
+    }
+}
+
+impl fmt::Display for VersionRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.operator, self.version)
+    }
+}
+
+impl FromStr for VersionRelation {
+    type Err = RelationError;
+
+    /// Parses the contents of the parentheses, e.g. `>= 2.34`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.find(|c| !matches!(c, '<' | '=' | '>')).unwrap_or(s.len());
+        let (operator, version) = s.split_at(split);
+
+        if operator.is_empty() {
+            return Err(RelationError::InvalidOperator);
+        }
+
+        let version = version.trim();
+        if version.is_empty() {
+            return Err(RelationError::MissingVersion);
+        }
+
+        Ok(Self {
+            operator: operator.parse()?,
+            version: version.parse()?,
+        })
+    }
+}
+
+/// A single entry of an architecture restriction list such as `[amd64 !i386]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArchitectureRestriction {
+    pub negated: bool,
+    pub architecture: String,
+}
+
+impl fmt::Display for ArchitectureRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}", self.architecture)
+    }
+}
+
+/// A single term of a build profile restriction formula such as `<!nocheck cross>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BuildProfile {
+    pub negated: bool,
+    pub name: String,
+}
+
+impl fmt::Display for BuildProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A single package in a relationship field, e.g. `foo:any (<< 1:2.0~) [amd64] <!nocheck>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    pub package: String,
+    /// Architecture qualifier following the package name, e.g. `any` in `foo:any`.
+    pub arch_qualifier: Option<String>,
+    pub version: Option<VersionRelation>,
+    /// Architecture restriction list, empty if there is none.
+    pub architectures: Vec<ArchitectureRestriction>,
+    /// Build profile restriction formula. The outer list is a disjunction of `<…>` groups, each
+    /// group is a conjunction of its terms.
+    pub profiles: Vec<Vec<BuildProfile>>,
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.package)?;
+
+        if let Some(ref qualifier) = self.arch_qualifier {
+            write!(f, ":{}", qualifier)?;
+        }
+
+        if let Some(ref version) = self.version {
+            write!(f, " ({})", version)?;
+        }
+
+        if !self.architectures.is_empty() {
+            write!(f, " [")?;
+            write_separated(f, &self.architectures, " ")?;
+            write!(f, "]")?;
+        }
+
+        for group in &self.profiles {
+            write!(f, " <")?;
+            write_separated(f, group, " ")?;
+            write!(f, ">")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Dependency {
+    type Err = RelationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(RelationError::Empty);
+        }
+
+        let name_end = s
+            .find(|c: char| c.is_whitespace() || matches!(c, ':' | '(' | '[' | '<'))
+            .unwrap_or(s.len());
+        let (package, mut rest) = s.split_at(name_end);
+
+        if !valid_package_name(package) {
+            return Err(RelationError::InvalidPackageName);
+        }
+
+        let mut arch_qualifier = None;
+        if let Some(qualified) = rest.strip_prefix(':') {
+            let end = qualified
+                .find(|c: char| c.is_whitespace() || matches!(c, '(' | '[' | '<'))
+                .unwrap_or(qualified.len());
+            let (qualifier, remainder) = qualified.split_at(end);
+
+            if !valid_architecture(qualifier) {
+                return Err(RelationError::InvalidArchitecture);
+            }
+
+            arch_qualifier = Some(qualifier.to_string());
+            rest = remainder;
+        }
+
+        rest = rest.trim_start();
+        let mut version = None;
+        if let Some(inner) = rest.strip_prefix('(') {
+            let (inner, remainder) = inner
+                .split_once(')')
+                .ok_or(RelationError::UnterminatedVersion)?;
+
+            version = Some(inner.parse()?);
+            rest = remainder.trim_start();
+        }
+
+        let mut architectures = Vec::new();
+        if let Some(inner) = rest.strip_prefix('[') {
+            let (inner, remainder) = inner
+                .split_once(']')
+                .ok_or(RelationError::UnterminatedArchitectures)?;
+
+            architectures = inner
+                .split_whitespace()
+                .map(|term| {
+                    let (negated, architecture) = negation(term);
+                    if valid_architecture(architecture) {
+                        Ok(ArchitectureRestriction {
+                            negated,
+                            architecture: architecture.to_string(),
+                        })
+                    } else {
+                        Err(RelationError::InvalidArchitecture)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if architectures.is_empty() {
+                return Err(RelationError::InvalidArchitecture);
+            }
+
+            rest = remainder.trim_start();
+        }
+
+        let mut profiles = Vec::new();
+        while let Some(inner) = rest.strip_prefix('<') {
+            let (inner, remainder) = inner
+                .split_once('>')
+                .ok_or(RelationError::UnterminatedProfiles)?;
+
+            let group = inner
+                .split_whitespace()
+                .map(|term| {
+                    let (negated, name) = negation(term);
+                    if valid_profile_name(name) {
+                        Ok(BuildProfile {
+                            negated,
+                            name: name.to_string(),
+                        })
+                    } else {
+                        Err(RelationError::InvalidProfile)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if group.is_empty() {
+                return Err(RelationError::InvalidProfile);
+            }
+
+            profiles.push(group);
+            rest = remainder.trim_start();
+        }
+
+        if !rest.is_empty() {
+            return Err(RelationError::TrailingCharacters);
+        }
+
+        Ok(Self {
+            package: package.to_string(),
+            arch_qualifier,
+            version,
+            architectures,
+            profiles,
+        })
+    }
+}
+
+/// A list of alternative [`Dependency`](crate::relation::Dependency)s separated by `|`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relation {
+    pub alternatives: Vec<Dependency>,
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_separated(f, &self.alternatives, " | ")
+    }
+}
+
+impl FromStr for Relation {
+    type Err = RelationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            alternatives: s
+                .split('|')
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+/// A complete relationship field, i.e. a comma separated list of
+/// [`Relation`](crate::relation::Relation)s.
+///
+/// Empty entries, as left behind by trailing commas, are ignored while parsing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelationField {
+    pub relations: Vec<Relation>,
+}
+
+impl fmt::Display for RelationField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_separated(f, &self.relations, ", ")
+    }
+}
+
+impl FromStr for RelationField {
+    type Err = RelationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            relations: s
+                .split(',')
+                .filter(|relation| !relation.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+fn write_separated<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    separator: &str,
+) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+
+    Ok(())
+}
+
+fn negation(term: &str) -> (bool, &str) {
+    match term.strip_prefix('!') {
+        Some(term) => (true, term),
+        None => (false, term),
+    }
+}
+
+/// Package names consist of at least two lower case letters, digits, `+`, `-` and `.` and must
+/// start with an alphanumeric character, see Policy 5.6.1.
+pub(crate) fn valid_package_name(s: &str) -> bool {
+    s.len() >= 2
+        && s.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+}
+
+pub(crate) fn valid_architecture(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn valid_profile_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_field() {
+        let field = "libc6 (>= 2.34), foo:any (<< 1:2.0~) | bar [amd64] <!nocheck>"
+            .parse::<RelationField>()
+            .unwrap();
+
+        assert_eq!(field.relations.len(), 2);
+
+        let libc = &field.relations[0].alternatives[0];
+        assert_eq!(libc.package, "libc6");
+        assert_eq!(
+            libc.version,
+            Some(VersionRelation {
+                operator: RelationOperator::LaterOrEqual,
+                version: "2.34".parse().unwrap(),
+            })
+        );
+
+        let foo = &field.relations[1].alternatives[0];
+        assert_eq!(foo.arch_qualifier.as_deref(), Some("any"));
+        assert_eq!(
+            foo.version.as_ref().map(|v| v.operator),
+            Some(RelationOperator::StrictlyEarlier)
+        );
+
+        let bar = &field.relations[1].alternatives[1];
+        assert_eq!(
+            bar.architectures,
+            vec![ArchitectureRestriction {
+                negated: false,
+                architecture: "amd64".to_string(),
+            }]
+        );
+        assert_eq!(
+            bar.profiles,
+            vec![vec![BuildProfile {
+                negated: true,
+                name: "nocheck".to_string(),
+            }]]
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let canonical =
+            "libc6 (>= 2.34), foo:any (<< 1:2.0~) | bar [amd64 !i386] <!nocheck> <stage1 cross>";
+        let field = canonical.parse::<RelationField>().unwrap();
+
+        assert_eq!(field.to_string(), canonical);
+        assert_eq!(field.to_string().parse::<RelationField>().unwrap(), field);
+    }
+
+    #[test]
+    fn parse_compact_and_trailing_comma() {
+        let field = "libc6(>=2.34),\n foo [!hurd-i386],"
+            .parse::<RelationField>()
+            .unwrap();
+
+        assert_eq!(field.to_string(), "libc6 (>= 2.34), foo [!hurd-i386]");
+    }
+
+    #[test]
+    fn satisfied_by() {
+        let version = |s: &str| s.parse::<DebianVersion>().unwrap();
+        let cases = [
+            ("<< 2.0", "2.0~rc1", true),
+            ("<< 2.0", "2.0", false),
+            ("<= 2.0", "2.0-0", true),
+            ("<= 2.0", "2.0-1", false),
+            ("= 1:2.0", "1:2.0", true),
+            ("= 1:2.0", "2.0", false),
+            (">= 2.34", "2.35", true),
+            (">= 2.34", "2.34~", false),
+            (">> 1.0", "1.0+b1", true),
+            (">> 1.0", "1.0", false),
+            ("< 2.0", "2.0", true),
+            ("> 2.0", "2.0", true),
+            ("> 2.0", "1.9", false),
+        ];
+
+        for (relation, candidate, expected) in cases {
+            let relation = relation.parse::<VersionRelation>().unwrap();
+            assert_eq!(
+                relation.satisfied_by(&version(candidate)),
+                expected,
+                "{} satisfies ({})",
+                candidate,
+                relation
+            );
+        }
+    }
+
+    #[test]
+    fn deprecated_operators() {
+        let relation = "foo (< 1.0)"
+            .parse::<Dependency>()
+            .unwrap()
+            .version
+            .unwrap();
+
+        assert!(relation.is_deprecated());
+        assert_eq!(relation.to_string(), "< 1.0");
+        assert!(!">= 1.0".parse::<VersionRelation>().unwrap().is_deprecated());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("".parse::<Dependency>(), Err(RelationError::Empty));
+        assert_eq!("aa | | bb".parse::<Relation>(), Err(RelationError::Empty));
+        assert_eq!(
+            "Foo".parse::<Dependency>(),
+            Err(RelationError::InvalidPackageName)
+        );
+        assert_eq!(
+            "a (>= 1.0)".parse::<Dependency>(),
+            Err(RelationError::InvalidPackageName)
+        );
+        assert_eq!(
+            "foo (~> 1.0)".parse::<Dependency>(),
+            Err(RelationError::InvalidOperator)
+        );
+        assert_eq!(
+            "foo (>= )".parse::<Dependency>(),
+            Err(RelationError::MissingVersion)
+        );
+        assert_eq!(
+            "foo (>= 1.0".parse::<Dependency>(),
+            Err(RelationError::UnterminatedVersion)
+        );
+        assert_eq!(
+            "foo [amd64".parse::<Dependency>(),
+            Err(RelationError::UnterminatedArchitectures)
+        );
+        assert_eq!(
+            "foo <!nocheck".parse::<Dependency>(),
+            Err(RelationError::UnterminatedProfiles)
+        );
+        assert_eq!(
+            "foo bar".parse::<Dependency>(),
+            Err(RelationError::TrailingCharacters)
+        );
+        assert!(matches!(
+            "foo (>= a1)".parse::<Dependency>(),
+            Err(RelationError::Version(_))
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! Conversions between [`DebianVersion`](crate::DebianVersion) and `semver::Version`, enabled
+//! with the `semver` feature.
+//!
+//! A pre-release becomes a `~` suffix, so that `1.0.0-rc.1` turns into `1.0.0~rc.1` and sorts
+//! before `1.0.0`, and build metadata becomes a `+` suffix. The conversion preserves the order of
+//! versions, except for identifiers mixing letters and digits such as `alpha10`, which semver
+//! compares as text and Debian compares numerically.
+//!
+//! Semver ignores build metadata when comparing, while Debian sorts `1.0.0+b` after `1.0.0`.
+//! This refines the order of semver without reversing it: versions that differ in more than
+//! their build metadata, such as `1.0.0+b` and `1.0.1`, keep their order.
+
+use ::semver::{BuildMetadata, Prerelease, Version};
+
+use crate::error::SemverError;
+use crate::validations::{ValidateDebianRevision, ValidateUpstreamVersion};
+use crate::DebianVersion;
+
+impl DebianVersion {
+    /// Converts a semantic version into the upstream version of a Debian version with the given
+    /// revision. Fails if the pre-release or build metadata contains a hyphen and there is no
+    /// revision, or if the revision is invalid.
+    pub fn from_semver(
+        version: &Version,
+        debian_revision: Option<&str>,
+    ) -> Result<DebianVersion, SemverError> {
+        let version = DebianVersion {
+            epoch: None,
+            upstream_version: upstream_version(version),
+            debian_revision: debian_revision.map(str::to_string),
+        };
+
+        match version.debian_revision {
+            Some(ref revision) => {
+                let offset = version.upstream_version.len() + 1;
+                revision
+                    .validate()
+                    .map_err(|error| error.relocate(&version.to_string(), offset))?;
+            }
+            None => {
+                version.upstream_version.validate_without_revision()?;
+            }
+        }
+
+        Ok(version)
+    }
+
+    /// Converts the version into a semantic version, returning it together with a flag that is
+    /// `true` if the conversion lost information, i.e. converting back with
+    /// [`from_semver`](crate::DebianVersion::from_semver) doesn't yield the same version. This
+    /// is the case for versions with an epoch or Debian revision, which are dropped, and for
+    /// upstream versions with fewer than three numbers or leading zeros. Debian orders build
+    /// metadata while semver ignores it, so such versions are lossy as well.
+    ///
+    /// Fails unless the upstream version is of the form `major[.minor[.patch]]`, optionally
+    /// followed by `~pre-release` and `+build` that are valid in semver.
+    pub fn to_semver(&self) -> Result<(Version, bool), SemverError> {
+        let upstream = self.upstream_version.as_str();
+        let (release, rest) =
+            upstream.split_at(upstream.find(['~', '+']).unwrap_or(upstream.len()));
+        let (pre, build) = match rest.strip_prefix('~') {
+            Some(rest) => rest.split_once('+').unwrap_or((rest, "")),
+            None => ("", rest.strip_prefix('+').unwrap_or_default()),
+        };
+
+        let invalid_release = || SemverError::InvalidRelease(upstream.to_string());
+        let mut numbers = [0; 3];
+        for (index, number) in release.split('.').enumerate() {
+            if index == numbers.len() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid_release());
+            }
+            numbers[index] = number.parse().map_err(|_| invalid_release())?;
+        }
+
+        let version = Version {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            pre: Prerelease::new(pre)
+                .map_err(|_| SemverError::InvalidPrerelease(pre.to_string()))?,
+            build: BuildMetadata::new(build)
+                .map_err(|_| SemverError::InvalidBuildMetadata(build.to_string()))?,
+        };
+        let lossy = self.epoch.is_some()
+            || self.debian_revision.is_some()
+            || !version.build.is_empty()
+            || upstream_version(&version) != upstream;
+
+        Ok((version, lossy))
+    }
+}
+
+impl TryFrom<&Version> for DebianVersion {
+    type Error = SemverError;
+
+    /// Converts a semantic version into a native version, see
+    /// [`DebianVersion::from_semver`](crate::DebianVersion::from_semver).
+    fn try_from(version: &Version) -> Result<Self, Self::Error> {
+        DebianVersion::from_semver(version, None)
+    }
+}
+
+/// Renders `version` as an upstream version.
+fn upstream_version(version: &Version) -> String {
+    let mut upstream = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        upstream.push('~');
+        upstream.push_str(&version.pre);
+    }
+    if !version.build.is_empty() {
+        upstream.push('+');
+        upstream.push_str(&version.build);
+    }
+
+    upstream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DebianVersionError;
+    use pretty_assertions::assert_eq;
+
+    fn semver(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn debian(s: &str) -> DebianVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn from_semver() {
+        assert_eq!(
+            DebianVersion::try_from(&semver("1.0.0-rc.1"))
+                .unwrap()
+                .to_string(),
+            "1.0.0~rc.1"
+        );
+        assert_eq!(
+            DebianVersion::from_semver(&semver("1.2.3-beta.2+build.5"), Some("1"))
+                .unwrap()
+                .to_string(),
+            "1.2.3~beta.2+build.5-1"
+        );
+        assert_eq!(
+            DebianVersion::from_semver(&semver("1.0.0-alpha-1"), Some("2"))
+                .unwrap()
+                .upstream_version,
+            "1.0.0~alpha-1"
+        );
+
+        let error = DebianVersion::try_from(&semver("1.0.0-alpha-1")).unwrap_err();
+        assert!(matches!(
+            error,
+            SemverError::Version(DebianVersionError::UpstreamInvalidCharacters(_))
+        ));
+        let SemverError::Version(error) =
+            DebianVersion::from_semver(&semver("1.0.0"), Some("1_1")).unwrap_err()
+        else {
+            panic!("expected a version error");
+        };
+        assert_eq!(error.span(), Some(7..8));
+    }
+
+    #[test]
+    fn preserves_order() {
+        // The example of the semver specification, section 11.
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.1.0-0",
+            "1.1.0",
+            "2.0.0",
+        ]
+        .map(|s| DebianVersion::try_from(&semver(s)).unwrap());
+
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+
+        // Build metadata only orders versions that semver considers equal.
+        let version = |s| DebianVersion::try_from(&semver(s)).unwrap();
+        assert!(version("1.0.0") < version("1.0.0+b"));
+        assert!(version("1.0.0+b") < version("1.0.1-rc.1"));
+        assert!(version("1.0.0-rc.1+b") < version("1.0.0"));
+    }
+
+    #[test]
+    fn to_semver() {
+        assert_eq!(
+            debian("1.0.0~rc.1").to_semver(),
+            Ok((semver("1.0.0-rc.1"), false))
+        );
+        assert_eq!(debian("2.1.3").to_semver(), Ok((semver("2.1.3"), false)));
+        assert_eq!(debian("1:2.1-3").to_semver(), Ok((semver("2.1.0"), true)));
+        assert_eq!(
+            debian("1.2.3~rc1+ds").to_semver(),
+            Ok((semver("1.2.3-rc1+ds"), true))
+        );
+        assert_eq!(debian("01.2.3").to_semver(), Ok((semver("1.2.3"), true)));
+
+        let (version, _) = debian("1.2.3~rc1+ds").to_semver().unwrap();
+        assert_eq!(
+            DebianVersion::try_from(&version),
+            Ok(debian("1.2.3~rc1+ds"))
+        );
+
+        assert_eq!(
+            debian("1.2.3.4").to_semver(),
+            Err(SemverError::InvalidRelease("1.2.3.4".to_string()))
+        );
+        assert_eq!(
+            debian("1.2a").to_semver(),
+            Err(SemverError::InvalidRelease("1.2a".to_string()))
+        );
+        assert_eq!(
+            debian("1.0~rc~1").to_semver(),
+            Err(SemverError::InvalidPrerelease("rc~1".to_string()))
+        );
+        assert_eq!(
+            debian("1.0+dfsg~1").to_semver(),
+            Err(SemverError::InvalidBuildMetadata("dfsg~1".to_string()))
+        );
+    }
+}
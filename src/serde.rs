@@ -0,0 +1,233 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! [`DebianVersion`](crate::DebianVersion), [`OptionalVersion`](crate::optional::OptionalVersion)
+//! and the relation types are serialized as their canonical string representation and validated
+//! through `FromStr` when deserialized. [`Epoch`](crate::Epoch) is serialized as an integer. Wrap
+//! a version in [`Structured`](crate::serde::Structured) to (de)serialize its components
+//! separately instead.
+
+use std::{borrow::Cow, fmt, marker::PhantomData, str::FromStr};
+
+use ::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::optional::OptionalVersion;
+use crate::relation::{Dependency, Relation, RelationField, RelationOperator, VersionRelation};
+use crate::{parse_version, DebianVersion, DebianVersionRef};
+
+/// Deserializes any type implementing `FromStr` from a string.
+struct FromStrVisitor<T>(PhantomData<T>);
+
+impl<T> de::Visitor<'_> for FromStrVisitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+macro_rules! serde_via_string {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_str(FromStrVisitor(PhantomData))
+                }
+            }
+        )*
+    };
+}
+
+serde_via_string!(
+    DebianVersion,
+    OptionalVersion,
+    RelationOperator,
+    VersionRelation,
+    Dependency,
+    Relation,
+    RelationField,
+);
+
+impl Serialize for DebianVersionRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for DebianVersionRef<'a> {
+    /// Only succeeds for formats that can lend out the input, since the components borrow from
+    /// it.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BorrowedVisitor;
+
+        impl<'de> de::Visitor<'de> for BorrowedVisitor {
+            type Value = DebianVersionRef<'de>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a borrowed string")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Self::Value, E> {
+                parse_version(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(BorrowedVisitor)
+    }
+}
+
+/// Wrapper (de)serializing a [`DebianVersion`](crate::DebianVersion) as a structure with the
+/// fields `epoch`, `upstream_version` and `debian_revision` instead of a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Structured(pub DebianVersion);
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "::serde")]
+struct Components<'a> {
+    #[serde(default)]
+    epoch: Option<usize>,
+    #[serde(borrow)]
+    upstream_version: Cow<'a, str>,
+    #[serde(borrow, default)]
+    debian_revision: Option<Cow<'a, str>>,
+}
+
+impl Serialize for Structured {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Components {
+            epoch: self.0.epoch,
+            upstream_version: Cow::Borrowed(&self.0.upstream_version),
+            debian_revision: self.0.debian_revision.as_deref().map(Cow::Borrowed),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Structured {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let components = Components::deserialize(deserializer)?;
+        let version = DebianVersion {
+            epoch: components.epoch,
+            upstream_version: components.upstream_version.into_owned(),
+            debian_revision: components.debian_revision.map(Cow::into_owned),
+        };
+
+        // Validate by parsing the canonical string, which must yield the same components.
+        let parsed = version
+            .to_string()
+            .parse::<DebianVersion>()
+            .map_err(de::Error::custom)?;
+        if parsed.upstream_version != version.upstream_version
+            || parsed.debian_revision != version.debian_revision
+        {
+            return Err(de::Error::custom(format!(
+                "components don't round-trip through {:?}",
+                version.to_string()
+            )));
+        }
+
+        Ok(Self(version))
+    }
+}
+
+impl From<DebianVersion> for Structured {
+    fn from(version: DebianVersion) -> Self {
+        Self(version)
+    }
+}
+
+impl From<Structured> for DebianVersion {
+    fn from(structured: Structured) -> Self {
+        structured.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Epoch;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn version_as_string() {
+        let version = "1:2.0~rc1-3".parse::<DebianVersion>().unwrap();
+        let json = serde_json::to_string(&version).unwrap();
+
+        assert_eq!(json, r#""1:2.0~rc1-3""#);
+        assert_eq!(
+            serde_json::from_str::<DebianVersion>(&json).unwrap(),
+            version
+        );
+        assert!(serde_json::from_str::<DebianVersion>(r#""a1.0""#).is_err());
+    }
+
+    #[test]
+    fn optional_version_as_string() {
+        let json = serde_json::to_string(&[OptionalVersion::NONE, "1.0-1".parse().unwrap()]);
+
+        assert_eq!(json.unwrap(), r#"["","1.0-1"]"#);
+        assert_eq!(
+            serde_json::from_str::<OptionalVersion>(r#""""#).unwrap(),
+            OptionalVersion::NONE
+        );
+    }
+
+    #[test]
+    fn borrowed_version() {
+        let json = r#""1:2.0-3""#;
+        let version = serde_json::from_str::<DebianVersionRef<'_>>(json).unwrap();
+
+        assert_eq!(version.upstream_version, "2.0");
+        assert_eq!(serde_json::to_string(&version).unwrap(), json);
+    }
+
+    #[test]
+    fn epoch_as_integer() {
+        assert_eq!(serde_json::to_string(&Epoch(2)).unwrap(), "2");
+        assert_eq!(serde_json::from_str::<Epoch>("2").unwrap(), Epoch(2));
+    }
+
+    #[test]
+    fn relations_as_string() {
+        let field = "libc6 (>= 2.34), foo | bar [amd64]"
+            .parse::<RelationField>()
+            .unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+
+        assert_eq!(json, r#""libc6 (>= 2.34), foo | bar [amd64]""#);
+        assert_eq!(serde_json::from_str::<RelationField>(&json).unwrap(), field);
+    }
+
+    #[test]
+    fn structured() {
+        let version = Structured("1:2.0-3".parse().unwrap());
+        let json = serde_json::to_string(&version).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"epoch":1,"upstream_version":"2.0","debian_revision":"3"}"#
+        );
+        assert_eq!(serde_json::from_str::<Structured>(&json).unwrap(), version);
+        assert_eq!(
+            serde_json::from_str::<Structured>(r#"{"upstream_version":"2.0"}"#)
+                .unwrap()
+                .0,
+            "2.0".parse().unwrap()
+        );
+        assert!(serde_json::from_str::<Structured>(r#"{"upstream_version":"2.0-1"}"#).is_err());
+        assert!(serde_json::from_str::<Structured>(r#"{"upstream_version":"x"}"#).is_err());
+    }
+}
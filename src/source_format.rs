@@ -0,0 +1,170 @@
+//! Source package formats and the version constraints they impose, see dpkg-source(1).
+
+use std::{fmt, str::FromStr};
+
+use crate::error::{Component, DebianVersionError, ErrorLocation, UnknownSourceFormat};
+use crate::{DebianVersion, Result};
+
+/// The format of a source package, as given in `debian/source/format` or the `Format` field of
+/// a `.dsc` file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SourceFormat {
+    /// `1.0`, which may be native or come with an orig tarball and a `.diff.gz`.
+    V1,
+    /// `3.0 (native)`
+    Native,
+    /// `3.0 (quilt)`
+    Quilt,
+    /// Any other well-formed format, e.g. `3.0 (git)` or `2.0`, which imposes no constraints on
+    /// the version.
+    Other(String),
+}
+
+impl SourceFormat {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            SourceFormat::V1 => "1.0",
+            SourceFormat::Native => "3.0 (native)",
+            SourceFormat::Quilt => "3.0 (quilt)",
+            SourceFormat::Other(ref format) => format,
+        }
+    }
+}
+
+impl fmt::Display for SourceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for SourceFormat {
+    type Err = UnknownSourceFormat;
+
+    /// Parses a format of the form `major.minor`, optionally followed by a variant in
+    /// parentheses, e.g. `3.0 (quilt)`. Formats dpkg-source doesn't know yield
+    /// [`SourceFormat::Other`](crate::source_format::SourceFormat::Other).
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, variant) = match s.split_once(' ') {
+            Some((number, variant)) => (number, Some(variant)),
+            None => (s, None),
+        };
+        let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        let well_formed = number
+            .split_once('.')
+            .is_some_and(|(major, minor)| is_number(major) && is_number(minor))
+            && variant.is_none_or(|variant| {
+                variant
+                    .strip_prefix('(')
+                    .and_then(|variant| variant.strip_suffix(')'))
+                    .is_some_and(|name| {
+                        !name.is_empty()
+                            && name
+                                .bytes()
+                                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+                    })
+            });
+
+        match s {
+            "1.0" => Ok(SourceFormat::V1),
+            "3.0 (native)" => Ok(SourceFormat::Native),
+            "3.0 (quilt)" => Ok(SourceFormat::Quilt),
+            _ if well_formed => Ok(SourceFormat::Other(s.to_string())),
+            _ => Err(UnknownSourceFormat(s.to_string())),
+        }
+    }
+}
+
+impl DebianVersion {
+    /// Checks that the version is acceptable for a source package of the given format: `3.0
+    /// (native)` packages must not have a Debian revision and `3.0 (quilt)` packages must have
+    /// one. Format `1.0` and other formats accept both.
+    pub fn validate_for_format(&self, format: &SourceFormat) -> Result<()> {
+        let input = self.to_string();
+
+        match (format, &self.debian_revision) {
+            (SourceFormat::Native, Some(revision)) => {
+                Err(DebianVersionError::NativeWithRevision(ErrorLocation::new(
+                    Component::DebianRevision,
+                    &input,
+                    input.len() - revision.len()..input.len(),
+                    None,
+                )))
+            }
+            (SourceFormat::Quilt, None) => {
+                Err(DebianVersionError::MissingRevision(ErrorLocation::new(
+                    Component::DebianRevision,
+                    &input,
+                    input.len()..input.len(),
+                    None,
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_formats() {
+        for format in [
+            SourceFormat::V1,
+            SourceFormat::Native,
+            SourceFormat::Quilt,
+            SourceFormat::Other("3.0 (git)".to_string()),
+            SourceFormat::Other("2.0".to_string()),
+        ] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        for invalid in [
+            "",
+            "quilt",
+            "3.0 quilt",
+            "3.0 ()",
+            "3 (native)",
+            "3.0 (Git)",
+        ] {
+            assert_eq!(
+                invalid.parse::<SourceFormat>(),
+                Err(UnknownSourceFormat(invalid.to_string())),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn validate_for_format() {
+        let native = "1:1.2".parse::<DebianVersion>().unwrap();
+        let quilt = "1:1.2-3".parse::<DebianVersion>().unwrap();
+
+        assert_eq!(native.validate_for_format(&SourceFormat::V1), Ok(()));
+        assert_eq!(quilt.validate_for_format(&SourceFormat::V1), Ok(()));
+        assert_eq!(native.validate_for_format(&SourceFormat::Native), Ok(()));
+        assert_eq!(quilt.validate_for_format(&SourceFormat::Quilt), Ok(()));
+        let git = SourceFormat::Other("3.0 (git)".to_string());
+        assert_eq!(native.validate_for_format(&git), Ok(()));
+        assert_eq!(quilt.validate_for_format(&git), Ok(()));
+
+        let error = quilt
+            .validate_for_format(&SourceFormat::Native)
+            .unwrap_err();
+        assert!(matches!(error, DebianVersionError::NativeWithRevision(_)));
+        assert_eq!(error.span(), Some(6..7));
+        assert_eq!(
+            error.to_string(),
+            "Native source packages must not have a Debian revision (at byte 6).\n  1:1.2-3\n        ^"
+        );
+
+        let error = native
+            .validate_for_format(&SourceFormat::Quilt)
+            .unwrap_err();
+        assert!(matches!(error, DebianVersionError::MissingRevision(_)));
+        assert_eq!(error.span(), Some(5..5));
+        assert_eq!(error.component(), Some(Component::DebianRevision));
+    }
+}
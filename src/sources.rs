@@ -0,0 +1,421 @@
+//! Parsing of source package descriptions, as found in `.dsc` files and APT `Sources` indexes.
+
+use std::{collections::BTreeMap, io::BufRead, path::Path, str::FromStr};
+
+use crate::changelog::Changelog;
+use crate::control::{relations, required, version};
+use crate::deb822::{self, Paragraph};
+use crate::error::{Deb822Error, IndexError, SourceError};
+use crate::filename::{ArtifactKind, ArtifactName};
+use crate::packages::open_index;
+use crate::relation::RelationField;
+use crate::source_format::SourceFormat;
+use crate::DebianVersion;
+
+/// An entry of a file list such as `Checksums-Sha256`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileChecksum {
+    pub checksum: String,
+    pub size: u64,
+    pub name: String,
+}
+
+/// A source package, described by a `.dsc` file or a paragraph of a `Sources` index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourcePackage {
+    pub package: String,
+    pub version: DebianVersion,
+    /// The `Format` field, e.g. for
+    /// [`validate_for_format`](crate::DebianVersion::validate_for_format).
+    pub format: SourceFormat,
+    /// The binary packages built from the source package.
+    pub binaries: Vec<String>,
+    pub build_depends: RelationField,
+    pub build_depends_indep: RelationField,
+    pub build_depends_arch: RelationField,
+    pub checksums_sha256: Vec<FileChecksum>,
+    /// The directory of the files relative to the archive root, only set in `Sources` indexes.
+    pub directory: Option<String>,
+    /// The complete paragraph, for access to the remaining fields.
+    pub paragraph: Paragraph,
+}
+
+impl SourcePackage {
+    /// Checks that the names of the listed files match the package name and version: an orig
+    /// tarball (and optional component tarballs) named after the upstream version and a
+    /// `.debian.tar.*` or `.diff.gz` named after the full version, or a single tarball named after
+    /// the full version for native packages. Epochs never appear in file names.
+    pub fn check_files(&self) -> Result<(), SourceError> {
+        let native = self.version.debian_revision.is_none();
+        let mut has_orig_tarball = false;
+
+        for file in &self.checksums_sha256 {
+            let (name, signature) = match file.name.strip_suffix(".asc") {
+                Some(name) => (name, true),
+                None => (file.name.as_str(), false),
+            };
+            let matches = match name.parse::<ArtifactName>() {
+                Ok(artifact) if artifact.package == self.package => match artifact.kind {
+                    ArtifactKind::OrigTarball { ref component, .. } => {
+                        has_orig_tarball |= component.is_none() && !signature;
+                        !native
+                            && artifact.version.upstream_version == self.version.upstream_version
+                    }
+                    ArtifactKind::DebianTarball(_) | ArtifactKind::DiffGz => {
+                        !native && artifact.version.without_epoch() == self.version.without_epoch()
+                    }
+                    ArtifactKind::NativeTarball(_) => {
+                        native && artifact.version.without_epoch() == self.version.without_epoch()
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+
+            if !matches {
+                return Err(SourceError::UnexpectedFile(file.name.clone()));
+            }
+        }
+
+        if !native && !has_orig_tarball {
+            return Err(SourceError::MissingOrigTarball(format!(
+                "{}_{}.orig",
+                self.package, self.version.upstream_version
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the package name and version match the newest entry of `changelog`. The
+    /// versions must be identical as text, so `1.01-1` doesn't match `1.1-1` although they compare
+    /// equal.
+    pub fn check_changelog(&self, changelog: &Changelog) -> Result<(), SourceError> {
+        let latest = changelog.latest().ok_or(SourceError::EmptyChangelog)?;
+
+        if latest.package != self.package {
+            return Err(SourceError::PackageMismatch(
+                self.package.clone(),
+                latest.package.clone(),
+            ));
+        }
+        let (version, latest_version) = (self.version.to_string(), latest.version.to_string());
+        if version != latest_version {
+            return Err(SourceError::VersionMismatch(version, latest_version));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<Paragraph> for SourcePackage {
+    type Error = Deb822Error;
+
+    /// Converts a `.dsc` paragraph, which names the package in the `Source` field, or a `Sources`
+    /// paragraph, which uses `Package`.
+    fn try_from(paragraph: Paragraph) -> Result<Self, Self::Error> {
+        let package = match paragraph.get("Source") {
+            Some(source) => source,
+            None => required(&paragraph, "Package")?,
+        };
+
+        Ok(Self {
+            package,
+            version: version(&paragraph)?.ok_or(Deb822Error::MissingField("Version"))?,
+            format: required(&paragraph, "Format")?.parse()?,
+            binaries: paragraph
+                .get("Binary")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|binary| !binary.is_empty())
+                .map(str::to_string)
+                .collect(),
+            build_depends: relations(&paragraph, "Build-Depends")?,
+            build_depends_indep: relations(&paragraph, "Build-Depends-Indep")?,
+            build_depends_arch: relations(&paragraph, "Build-Depends-Arch")?,
+            checksums_sha256: file_list(&paragraph, "Checksums-Sha256")?,
+            directory: paragraph.get("Directory"),
+            paragraph,
+        })
+    }
+}
+
+impl FromStr for SourcePackage {
+    type Err = Deb822Error;
+
+    /// Parses a `.dsc` file. The OpenPGP signature, if any, is not verified.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<Paragraph>()?.try_into()
+    }
+}
+
+/// The source packages of one or more `Sources` indexes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourcesIndex {
+    /// The source packages by name, sorted by version in ascending order.
+    sources: BTreeMap<String, Vec<SourcePackage>>,
+}
+
+impl SourcesIndex {
+    /// Reads an uncompressed `Sources` index.
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, IndexError> {
+        let mut index = Self::default();
+        index.extend(reader)?;
+
+        Ok(index)
+    }
+
+    /// Reads a `Sources` index from a file, decompressing it according to the extension.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, IndexError> {
+        Self::read(open_index(path.as_ref())?)
+    }
+
+    /// Adds the source packages of another uncompressed `Sources` index.
+    pub fn extend<R: BufRead>(&mut self, reader: R) -> Result<(), IndexError> {
+        for paragraph in deb822::paragraphs(reader) {
+            let (line, paragraph) = paragraph?;
+            self.insert(
+                SourcePackage::try_from(paragraph)
+                    .map_err(|error| IndexError::Paragraph(line, error))?,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Adds a source package, keeping the versions of the package sorted.
+    pub fn insert(&mut self, source: SourcePackage) {
+        let versions = self.sources.entry(source.package.clone()).or_default();
+        let position = versions.partition_point(|existing| existing.version <= source.version);
+        versions.insert(position, source);
+    }
+
+    /// Returns the names of all source packages in alphabetical order.
+    pub fn package_names(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    /// Returns the number of distinct source package names.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Returns all versions of the source package called `package` in ascending order.
+    pub fn versions(&self, package: &str) -> &[SourcePackage] {
+        self.sources.get(package).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the newest version of the source package called `package`.
+    pub fn newest(&self, package: &str) -> Option<&SourcePackage> {
+        self.versions(package).last()
+    }
+}
+
+impl FromStr for SourcesIndex {
+    type Err = IndexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::read(s.as_bytes())
+    }
+}
+
+/// Parses a file list such as `Checksums-Sha256`, one `checksum size name` entry per
+/// continuation line. Missing fields are empty.
+fn file_list(paragraph: &Paragraph, name: &'static str) -> Result<Vec<FileChecksum>, Deb822Error> {
+    paragraph
+        .get(name)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_file_checksum(line).ok_or(Deb822Error::InvalidFileList(name)))
+        .collect()
+}
+
+fn parse_file_checksum(line: &str) -> Option<FileChecksum> {
+    let mut parts = line.split_whitespace();
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(checksum), Some(size), Some(name), None) => Some(FileChecksum {
+            checksum: checksum.to_string(),
+            size: size.parse().ok()?,
+            name: name.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::UnknownSourceFormat;
+    use pretty_assertions::assert_eq;
+
+    const DSC: &str = "\
+-----BEGIN PGP SIGNED MESSAGE-----
+Hash: SHA256
+
+Format: 3.0 (quilt)
+Source: versian
+Binary: versian, versian-doc
+Architecture: any all
+Version: 1:0.2.0-1
+Maintainer: Jane Doe <jane@example.org>
+Build-Depends: debhelper-compat (= 13), cargo
+Package-List:
+ versian deb rust optional arch=any
+Checksums-Sha256:
+ 0b3d7c4e 10240 versian_0.2.0.orig.tar.xz
+ 9f86d081 2048 versian_0.2.0-1.debian.tar.xz
+Files:
+ d41d8cd9 10240 versian_0.2.0.orig.tar.xz
+ a87ff679 2048 versian_0.2.0-1.debian.tar.xz
+
+-----BEGIN PGP SIGNATURE-----
+
+iQIzBAEBCAAdFiEE
+-----END PGP SIGNATURE-----
+";
+
+    const CHANGELOG: &str = "\
+versian (1:0.2.0-1) unstable; urgency=medium
+
+  * New upstream release.
+
+ -- Jane Doe <jane@example.org>  Mon, 24 Jul 2023 10:00:00 +0200
+";
+
+    #[test]
+    fn parse_dsc() {
+        let dsc = DSC.parse::<SourcePackage>().unwrap();
+
+        assert_eq!(dsc.package, "versian");
+        assert_eq!(dsc.version, "1:0.2.0-1".parse().unwrap());
+        assert_eq!(dsc.format, SourceFormat::Quilt);
+        assert_eq!(dsc.version.validate_for_format(&dsc.format), Ok(()));
+        assert_eq!(dsc.binaries, ["versian", "versian-doc"]);
+        assert_eq!(
+            dsc.build_depends.to_string(),
+            "debhelper-compat (= 13), cargo"
+        );
+        assert_eq!(
+            dsc.checksums_sha256[1],
+            FileChecksum {
+                checksum: "9f86d081".to_string(),
+                size: 2048,
+                name: "versian_0.2.0-1.debian.tar.xz".to_string()
+            }
+        );
+        assert_eq!(dsc.directory, None);
+    }
+
+    #[test]
+    fn checks() {
+        let mut dsc = DSC.parse::<SourcePackage>().unwrap();
+        let changelog = CHANGELOG.parse::<Changelog>().unwrap();
+        assert_eq!(dsc.check_files(), Ok(()));
+        assert_eq!(dsc.check_changelog(&changelog), Ok(()));
+
+        dsc.version = "1:0.2.1-1".parse().unwrap();
+        assert_eq!(
+            dsc.check_files(),
+            Err(SourceError::UnexpectedFile(
+                "versian_0.2.0.orig.tar.xz".to_string()
+            ))
+        );
+        assert_eq!(
+            dsc.check_changelog(&changelog),
+            Err(SourceError::VersionMismatch(
+                "1:0.2.1-1".to_string(),
+                "1:0.2.0-1".to_string()
+            ))
+        );
+
+        // Versions comparing equal must still be spelled the same.
+        let changelog = CHANGELOG
+            .replace("1:0.2.0-1", "1.01-1")
+            .parse::<Changelog>()
+            .unwrap();
+        dsc.version = "1.1-1".parse().unwrap();
+        assert_eq!(
+            dsc.check_changelog(&changelog),
+            Err(SourceError::VersionMismatch(
+                "1.1-1".to_string(),
+                "1.01-1".to_string()
+            ))
+        );
+
+        dsc.version = "1:0.2.0-1".parse().unwrap();
+        dsc.checksums_sha256.remove(0);
+        assert_eq!(
+            dsc.check_files(),
+            Err(SourceError::MissingOrigTarball(
+                "versian_0.2.0.orig".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn native_package() {
+        let dsc = "Format: 3.0 (native)\nSource: versian\nVersion: 2:0.2.0\nChecksums-Sha256:\n 0b3d7c4e 10240 versian_0.2.0.tar.xz\n"
+            .parse::<SourcePackage>()
+            .unwrap();
+
+        assert_eq!(dsc.check_files(), Ok(()));
+    }
+
+    #[test]
+    fn sources_index() {
+        let index = "\
+Package: versian
+Binary: versian
+Version: 0.2.0-1
+Format: 3.0 (quilt)
+Directory: pool/main/v/versian
+
+Package: versian
+Binary: versian
+Version: 0.1.0-2
+Format: 3.0 (quilt)
+Directory: pool/main/v/versian
+
+Package: versian-git
+Binary: versian
+Version: 0.3.0~git1-1
+Format: 3.0 (git)
+Directory: pool/main/v/versian-git
+"
+        .parse::<SourcesIndex>()
+        .unwrap();
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(
+            index.newest("versian-git").unwrap().format,
+            SourceFormat::Other("3.0 (git)".to_string())
+        );
+        let newest = index.newest("versian").unwrap();
+        assert_eq!(newest.version.to_string(), "0.2.0-1");
+        assert_eq!(newest.directory.as_deref(), Some("pool/main/v/versian"));
+
+        assert!(matches!(
+            "Package: a\nVersion: 1\nFormat: 1.0\nChecksums-Sha256:\n abc def a.tar.gz\n"
+                .parse::<SourcesIndex>(),
+            Err(IndexError::Paragraph(
+                1,
+                Deb822Error::InvalidFileList("Checksums-Sha256")
+            ))
+        ));
+        assert_eq!(
+            "Source: versian\nVersion: 1.0\nFormat: quilt\n".parse::<SourcePackage>(),
+            Err(Deb822Error::SourceFormat(UnknownSourceFormat(
+                "quilt".to_string()
+            )))
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::error::DebianVersionError;
+use crate::error::{Component, DebianVersionError, ErrorLocation};
 
 pub trait ValidateUpstreamVersion {
     fn validate_with_revision(&self) -> Result<bool, DebianVersionError>;
@@ -13,20 +13,25 @@ where
         let s = self.as_ref();
 
         if s.is_empty() {
-            return Err(DebianVersionError::EmptyUpstream);
+            return Err(DebianVersionError::EmptyUpstream(empty_location(
+                Component::UpstreamVersion,
+                s,
+            )));
         }
 
         if !s.starts_with(|c| char::is_ascii_digit(&c)) {
-            return Err(DebianVersionError::UpstreamStartWithDigit);
+            return Err(DebianVersionError::UpstreamStartWithDigit(
+                ErrorLocation::at_char(Component::UpstreamVersion, s, 0),
+            ));
         }
 
-        if s.chars().all(|c| {
-            char::is_ascii_alphanumeric(&c)
-                || ['.', '+', '-', ':', '~'].iter().any(|valid| *valid == c)
-        }) {
-            return Ok(true);
-        } else {
-            Err(DebianVersionError::UpstreamInvalidCharacters)
+        match s
+            .find(|c| !(char::is_ascii_alphanumeric(&c) || ['.', '+', '-', ':', '~'].contains(&c)))
+        {
+            None => Ok(true),
+            Some(index) => Err(DebianVersionError::UpstreamInvalidCharacters(
+                ErrorLocation::at_char(Component::UpstreamVersion, s, index),
+            )),
         }
     }
 
@@ -34,17 +39,23 @@ where
         let s = self.as_ref();
 
         if s.is_empty() {
-            return Err(DebianVersionError::EmptyUpstream);
+            return Err(DebianVersionError::EmptyUpstream(empty_location(
+                Component::UpstreamVersion,
+                s,
+            )));
         }
 
         if !s.starts_with(|c| char::is_ascii_digit(&c)) {
-            return Err(DebianVersionError::UpstreamStartWithDigit);
+            return Err(DebianVersionError::UpstreamStartWithDigit(
+                ErrorLocation::at_char(Component::UpstreamVersion, s, 0),
+            ));
         }
 
-        if only_valid_chars(s) {
-            return Ok(true);
-        } else {
-            Err(DebianVersionError::UpstreamInvalidCharacters)
+        match first_invalid_char(s) {
+            None => Ok(true),
+            Some(index) => Err(DebianVersionError::UpstreamInvalidCharacters(
+                ErrorLocation::at_char(Component::UpstreamVersion, s, index),
+            )),
         }
     }
 }
@@ -60,17 +71,37 @@ where
     fn validate(&self) -> Result<bool, DebianVersionError> {
         let s = self.as_ref();
         if s.is_empty() {
-            return Err(DebianVersionError::EmptyRevision);
+            return Err(DebianVersionError::EmptyRevision(empty_location(
+                Component::DebianRevision,
+                s,
+            )));
         }
-        if !only_valid_chars(s) {
-            return Err(DebianVersionError::RevisionInvalidCharacters);
+        if let Some(index) = s.find(|c| !is_valid_revision_char(c)) {
+            return Err(DebianVersionError::RevisionInvalidCharacters(
+                ErrorLocation::at_char(Component::DebianRevision, s, index),
+            ));
         }
 
         Ok(true)
     }
 }
 
-pub(crate) fn only_valid_chars(s: &str) -> bool {
-    s.chars()
-        .all(|c| char::is_ascii_alphanumeric(&c) || c == '+' || c == '.' || c == '~' || c == ':')
+pub(crate) fn is_valid_char(c: char) -> bool {
+    char::is_ascii_alphanumeric(&c) || c == '+' || c == '.' || c == '~' || c == ':'
+}
+
+/// Like [`is_valid_char`](crate::validations::is_valid_char), but without `:`, which may only
+/// appear in the upstream version.
+fn is_valid_revision_char(c: char) -> bool {
+    c != ':' && is_valid_char(c)
+}
+
+/// Returns the byte index of the first character rejected by
+/// [`is_valid_char`](crate::validations::is_valid_char).
+pub(crate) fn first_invalid_char(s: &str) -> Option<usize> {
+    s.find(|c| !is_valid_char(c))
+}
+
+fn empty_location(component: Component, s: &str) -> ErrorLocation {
+    ErrorLocation::new(component, s, 0..0, None)
 }